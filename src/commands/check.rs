@@ -1,13 +1,149 @@
-use anyhow::{bail, Context, Result};
+use crate::installer;
+use crate::system;
+use anyhow::{bail, Result};
+use serde::Serialize;
 use std::fs;
+use std::path::Path;
 
-pub fn check(verbose: bool) -> Result<()> {
-    println!("[Slate] Checking system requirements...");
+const DISPLAY_MANAGERS: [&str; 6] = ["gdm", "sddm", "lightdm", "lxdm", "greetd", "ly"];
+
+/// Where `check` is running, which decides what its findings are about.
+#[derive(Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+enum Host {
+    /// The Arch ISO: checks describe install readiness, not the target.
+    LiveIso,
+    /// A system Slate installed: checks describe this system.
+    Installed,
+    /// Any other Arch system.
+    Other,
+}
+
+impl Host {
+    fn detect() -> Self {
+        if Path::new("/run/archiso").exists() {
+            Host::LiveIso
+        } else if Path::new(installer::INSTALLED_PLAN_PATH).exists() {
+            Host::Installed
+        } else {
+            Host::Other
+        }
+    }
+}
+
+/// Everything `check` looked at, in the shape `--json` prints.
+#[derive(Serialize)]
+struct Report {
+    host: Host,
+    arch: bool,
+    root: bool,
+    uefi: bool,
+    /// Display managers enabled alongside Slate's tty1 autologin. Only a warning.
+    display_manager_conflicts: Vec<&'static str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    network: Option<Vec<NetworkProbe>>,
+}
+
+#[derive(Serialize)]
+struct NetworkProbe {
+    name: &'static str,
+    ok: bool,
+    detail: String,
+}
+
+impl Report {
+    fn collect(host: Host, network: bool) -> Self {
+        let arch = fs::read_to_string("/etc/os-release")
+            .map(|os_release| os_release.contains("ID=arch") || os_release.contains("ID=archarm"))
+            .unwrap_or(false);
+
+        // On the live ISO enabled services describe the ISO, not the target.
+        let display_manager_conflicts =
+            if host != Host::LiveIso && Path::new(installer::AUTOLOGIN_OVERRIDE_PATH).exists() {
+                DISPLAY_MANAGERS
+                    .into_iter()
+                    .filter(|dm| installer::service_enabled(dm))
+                    .collect()
+            } else {
+                Vec::new()
+            };
+
+        let network = network.then(|| {
+            system::network::diagnose()
+                .into_iter()
+                .map(|probe| match probe.result {
+                    Ok(detail) => NetworkProbe {
+                        name: probe.name,
+                        ok: true,
+                        detail,
+                    },
+                    Err(err) => NetworkProbe {
+                        name: probe.name,
+                        ok: false,
+                        detail: format!("{:#}", err),
+                    },
+                })
+                .collect()
+        });
+
+        Self {
+            host,
+            arch,
+            root: nix::unistd::Uid::effective().is_root(),
+            uefi: Path::new("/sys/firmware/efi").exists(),
+            display_manager_conflicts,
+            network,
+        }
+    }
+
+    /// Names of the hard requirements that failed.
+    fn failures(&self) -> Vec<&'static str> {
+        let mut failed = Vec::new();
+        for (name, ok) in [
+            ("arch", self.arch),
+            ("root", self.root),
+            ("uefi", self.uefi),
+        ] {
+            if !ok {
+                failed.push(name);
+            }
+        }
+        for probe in self.network.iter().flatten() {
+            if !probe.ok {
+                failed.push(probe.name);
+            }
+        }
+        failed
+    }
+}
+
+pub fn check(verbose: bool, network: bool, json: bool) -> Result<()> {
+    let host = Host::detect();
+    if json {
+        let report = Report::collect(host, network);
+        println!("{}", serde_json::to_string_pretty(&report)?);
+        let failed = report.failures();
+        if !failed.is_empty() {
+            bail!("System check failed: {}", failed.join(", "));
+        }
+        return Ok(());
+    }
+
+    match host {
+        Host::LiveIso => {
+            println!("[Slate] Checking installer environment (live ISO)...");
+            if Path::new(installer::TARGET_PLAN_PATH).exists() {
+                println!("  A target system is mounted at /mnt; it is not inspected here.");
+                println!("  Reboot into it and run `slate check` again to check it.");
+            }
+        }
+        Host::Installed => println!("[Slate] Checking installed Slate system..."),
+        Host::Other => println!("[Slate] Checking system requirements..."),
+    }
+    let report = Report::collect(host, network);
 
     // 1. Confirm Arch Linux (Live ISO or existing Arch)
-    let os_release =
-        fs::read_to_string("/etc/os-release").context("Failed to read /etc/os-release")?;
-    if !os_release.contains("ID=arch") && !os_release.contains("ID=archarm") {
+    if !report.arch {
         bail!("Slate requires Arch Linux. This system is not Arch.");
     }
     if verbose {
@@ -15,7 +151,7 @@ pub fn check(verbose: bool) -> Result<()> {
     }
 
     // 2. Check Root
-    if !nix::unistd::Uid::effective().is_root() {
+    if !report.root {
         bail!("This operation requires ROOT privileges.");
     }
     if verbose {
@@ -23,13 +159,74 @@ pub fn check(verbose: bool) -> Result<()> {
     }
 
     // 3. Check UEFI
-    if !std::path::Path::new("/sys/firmware/efi").exists() {
+    if !report.uefi {
         bail!("Legacy BIOS detected. Slate requires UEFI mode.");
     }
     if verbose {
         println!("✓ UEFI mode verified");
     }
 
-    println!("\n[Slate] System check complete. Ready for installation.");
+    // 4. Warn when a display manager competes with Slate's tty1 autologin.
+    if host != Host::LiveIso && Path::new(installer::AUTOLOGIN_OVERRIDE_PATH).exists() {
+        let enabled = &report.display_manager_conflicts;
+        if enabled.is_empty() {
+            if verbose {
+                println!("✓ No display manager conflicts with tty1 autologin");
+            }
+        } else {
+            println!(
+                "! Display manager enabled alongside Slate's tty1 autologin: {}",
+                enabled.join(", ")
+            );
+            println!("  This can cause a double login or a black screen. Keep one of them:");
+            for dm in enabled {
+                println!("    sudo systemctl disable {}", dm);
+            }
+            println!("    sudo rm {}", installer::AUTOLOGIN_OVERRIDE_PATH);
+        }
+    }
+
+    // 5. Network, layer by layer
+    if let Some(probes) = &report.network {
+        let mut failed = Vec::new();
+        for probe in probes {
+            if probe.ok {
+                println!("✓ {}: {}", probe.name, probe.detail);
+            } else {
+                println!("✗ {}: {}", probe.name, probe.detail);
+                failed.push(probe.name);
+            }
+        }
+        if !failed.is_empty() {
+            bail!("Network checks failed: {}", failed.join(", "));
+        }
+    }
+
+    match host {
+        Host::Installed => println!("\n[Slate] System check complete."),
+        _ => println!("\n[Slate] System check complete. Ready for installation."),
+    }
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{Host, NetworkProbe, Report};
+
+    #[test]
+    fn failures_ignore_display_manager_warnings() {
+        let report = Report {
+            host: Host::Installed,
+            arch: true,
+            root: false,
+            uefi: true,
+            display_manager_conflicts: vec!["sddm"],
+            network: Some(vec![NetworkProbe {
+                name: "DNS",
+                ok: false,
+                detail: "timed out".to_string(),
+            }]),
+        };
+        assert_eq!(report.failures(), vec!["root", "DNS"]);
+    }
+}
@@ -1,12 +1,22 @@
+use crate::error::{Classify, SlateError};
+use crate::installer::{self, InstallOptions};
 use crate::system;
 use crate::tui;
 use anyhow::{bail, Context, Result};
 
-pub fn forge() -> Result<()> {
-    let devices = system::list_block_devices().context("Failed to list block devices")?;
-    if devices.is_empty() {
-        bail!("No installable block devices found");
-    }
+pub fn forge(options: InstallOptions) -> Result<()> {
+    let devices = (|| -> Result<_> {
+        let devices = system::list_block_devices().context("Failed to list block devices")?;
+        if devices.is_empty() {
+            bail!("No installable block devices found");
+        }
+        Ok(devices)
+    })()
+    .classify(SlateError::Environment)?;
 
-    tui::run_installer(devices)
+    tui::run_installer(devices, options)
+}
+
+pub fn forge_resume(disk: &str) -> Result<()> {
+    installer::resume_install(disk)
 }
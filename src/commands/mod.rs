@@ -5,5 +5,5 @@ mod repair;
 
 pub use check::check;
 pub use chroot_stage::chroot_stage;
-pub use forge::forge;
+pub use forge::{forge, forge_resume};
 pub use repair::repair;
@@ -1,6 +1,6 @@
 use crate::installer;
 use anyhow::Result;
 
-pub fn repair() -> Result<()> {
-    installer::repair()
+pub fn repair(check_only: bool) -> Result<()> {
+    installer::repair(check_only)
 }
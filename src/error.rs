@@ -0,0 +1,114 @@
+use anyhow::Error;
+use thiserror::Error as ThisError;
+
+/// Failure classes that map to stable process exit codes.
+///
+/// Each variant wraps the underlying error transparently, so messages and
+/// `--json` output are unchanged; only the exit status carries the class.
+#[derive(Debug, ThisError)]
+pub enum SlateError {
+    /// Invalid command-line values or install plan.
+    #[error(transparent)]
+    Validation(Error),
+    /// The host is not ready: not root, not Arch, not UEFI, no target disks.
+    #[error(transparent)]
+    Environment(Error),
+    /// A disk, partition or mount could not be found or prepared.
+    #[error(transparent)]
+    Device(Error),
+    /// An install stage failed after the disk was touched.
+    #[error(transparent)]
+    Install(Error),
+    /// One or more repair groups failed.
+    #[error(transparent)]
+    Repair(Error),
+    /// `repair --check` found groups that need repair.
+    #[error(transparent)]
+    Drift(Error),
+}
+
+/// Exit codes as listed in `slate --help`. 2 is clap's usage error code.
+pub const EXIT_CODES: [(u8, &str); 8] = [
+    (1, "unexpected error"),
+    (2, "invalid command-line usage"),
+    (3, "invalid option value or install plan"),
+    (4, "environment not ready (root, Arch, UEFI, disks)"),
+    (5, "disk, partition or mount problem"),
+    (6, "install stage failed"),
+    (7, "repair finished with failed groups"),
+    (8, "repair --check found something to repair"),
+];
+
+impl SlateError {
+    pub fn exit_code(&self) -> u8 {
+        match self {
+            Self::Validation(_) => 3,
+            Self::Environment(_) => 4,
+            Self::Device(_) => 5,
+            Self::Install(_) => 6,
+            Self::Repair(_) => 7,
+            Self::Drift(_) => 8,
+        }
+    }
+}
+
+/// Exit code for an error: the first class found in its chain, otherwise 1.
+pub fn exit_code(err: &Error) -> u8 {
+    classified(err).map(SlateError::exit_code).unwrap_or(1)
+}
+
+fn classified(err: &Error) -> Option<&SlateError> {
+    err.chain()
+        .find_map(|cause| cause.downcast_ref::<SlateError>())
+}
+
+pub trait Classify<T> {
+    /// Tag an error with `class` unless a more specific site already classified it.
+    fn classify(self, class: fn(Error) -> SlateError) -> anyhow::Result<T>;
+}
+
+impl<T> Classify<T> for anyhow::Result<T> {
+    fn classify(self, class: fn(Error) -> SlateError) -> anyhow::Result<T> {
+        self.map_err(|err| {
+            if classified(&err).is_some() {
+                err
+            } else {
+                class(err).into()
+            }
+        })
+    }
+}
+
+pub fn exit_codes_help() -> String {
+    let mut help = String::from("Exit codes:\n  0  success\n");
+    for (code, meaning) in EXIT_CODES {
+        help.push_str(&format!("  {}  {}\n", code, meaning));
+    }
+    help
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{exit_code, Classify, SlateError};
+    use anyhow::{anyhow, bail, Context};
+
+    #[test]
+    fn classify_keeps_the_most_specific_class_and_message() {
+        let inner = (|| -> anyhow::Result<()> { bail!("must be run as root") })()
+            .classify(SlateError::Environment);
+        let err = inner
+            .classify(SlateError::Repair)
+            .context("repair")
+            .unwrap_err();
+
+        assert_eq!(exit_code(&err), 4);
+        assert_eq!(format!("{:#}", err), "repair: must be run as root");
+        assert_eq!(exit_code(&anyhow!("plain")), 1);
+
+        let drift: anyhow::Result<()> = Err(SlateError::Drift(anyhow!("Repair needed")).into());
+        assert_eq!(
+            exit_code(&drift.classify(SlateError::Repair).unwrap_err()),
+            8
+        );
+    }
+}
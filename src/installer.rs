@@ -1,26 +1,49 @@
-use crate::system;
+use crate::error::{Classify, SlateError};
+use crate::system::{self, privileged, TempPath, WriteOptions};
 use anyhow::{anyhow, bail, Context, Result};
 use serde::{Deserialize, Serialize};
+use std::cell::OnceCell;
 use std::collections::HashSet;
 use std::fs;
 use std::io::{self, BufRead, BufReader, Write};
 use std::os::unix::fs::MetadataExt;
-use std::os::unix::fs::PermissionsExt;
 use std::path::{Path, PathBuf};
 use std::process::{Command, Stdio};
-use std::sync::mpsc::Sender;
+use std::sync::mpsc::{self, Sender};
 use std::thread;
 use std::time::{Duration, Instant};
 
 pub const TARGET_ROOT: &str = "/mnt";
 pub const TARGET_PLAN_PATH: &str = "/mnt/etc/slate/install-plan.json";
+pub const INSTALLED_PLAN_PATH: &str = "/etc/slate/install-plan.json";
 const TARGET_CHECKPOINT_PATH: &str = "/mnt/etc/slate/checkpoint.json";
 const HOST_PLAN_PATH: &str = "/tmp/slate-install-plan.json";
+pub const PLAN_VERSION: u32 = 2;
 const SHELL_ARCHIVE_URL: &str =
     "https://github.com/manpreet113/shell/archive/refs/heads/main.tar.gz";
-const SHELL_REPO_DIR: &str = "/tmp/slate-shell";
+const PACKAGE_MANIFEST_PATH: &str = "/etc/slate/packages.toml";
+const DEFAULT_PACKAGE_MANIFEST: &str = include_str!("packages.toml");
+// /var/tmp is disk-backed; /tmp is often a small tmpfs, especially inside arch-chroot.
+const SHELL_STAGING_DIR: &str = "/var/tmp";
+const SHELL_REPO_DIR: &str = "/var/tmp/slate-shell";
+const SHELL_ARCHIVE_PATH: &str = "/var/tmp/slate-shell.tar.gz";
+const MIN_STAGING_BYTES: u64 = 512 * 1024 * 1024;
 const AX_BINARY_URL: &str = "https://github.com/manpreet113/ax/releases/latest/download/ax";
 const TEMP_AX_SUDOERS_FILE: &str = "/etc/sudoers.d/10-slate-ax";
+const LOADER_CONF_PATH: &str = "/boot/loader/loader.conf";
+const BOOT_ENTRY_PATH: &str = "/boot/loader/entries/slate.conf";
+const MKINITCPIO_CONF_PATH: &str = "/etc/mkinitcpio.conf";
+const INSTALL_SUMMARY_PATH: &str = "/etc/slate/install-summary.txt";
+const DEFAULT_LOCALE: &str = "en_US.UTF-8";
+const BTRFS_MOUNT_OPTIONS: &str = "rw,noatime,compress=zstd,space_cache=v2";
+pub const AUTOLOGIN_OVERRIDE_PATH: &str = "/etc/systemd/system/getty@tty1.service.d/autologin.conf";
+const MAX_LISTED_PATHS: usize = 20;
+const ESP_PARTTYPE: &str = "c12a7328-f81f-11d2-ba4b-00a0c93ec93b";
+const MIN_PRESERVED_ESP_BYTES: u64 = 512 * 1024 * 1024;
+/// Room for the kernel, microcode and both initramfs images on a shared ESP.
+const MIN_PRESERVED_ESP_FREE_BYTES: u64 = 256 * 1024 * 1024;
+const ESP_CHECK_MOUNT: &str = "/run/slate-esp-check";
+const AUTOLOGIN_MARKER: &str = "# Managed by Slate; removed when autologin is disabled.";
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct InstallPlan {
@@ -33,6 +56,26 @@ pub struct InstallPlan {
     pub git_name: String,
     pub git_email: String,
     pub desktop_profile: String,
+    /// LANG for the new system; en_US.UTF-8 is always generated alongside it.
+    #[serde(default = "default_locale")]
+    pub locale: String,
+    #[serde(default = "default_subvolumes")]
+    pub subvolumes: Vec<Subvolume>,
+    #[serde(default = "default_autologin")]
+    pub autologin: bool,
+    /// Keep partition 1 as an existing ESP instead of recreating it.
+    #[serde(default)]
+    pub preserve_esp: bool,
+    #[serde(default = "default_btrfs_options")]
+    pub btrfs_options: String,
+    #[serde(default = "default_boot_title")]
+    pub boot_title: String,
+    /// Seconds the systemd-boot menu waits; 0 hides it.
+    #[serde(default = "default_boot_timeout")]
+    pub boot_timeout: u32,
+    /// Plans written before the field existed read as version 1.
+    #[serde(default = "legacy_plan_version")]
+    pub version: u32,
 }
 
 impl InstallPlan {
@@ -51,8 +94,284 @@ impl InstallPlan {
                 bail!("{} cannot be empty", name);
             }
         }
+        validate_hostname(&self.hostname)?;
+        validate_username(&self.username)?;
+        validate_subvolumes(&self.subvolumes)?;
+        validate_btrfs_options(&self.btrfs_options)?;
+        validate_boot_title(&self.boot_title)?;
+        validate_locale(&self.locale)?;
         Ok(())
     }
+
+    /// Upgrade a plan read from disk to [`PLAN_VERSION`], returning whether it changed.
+    pub fn migrate(&mut self) -> Result<bool> {
+        if self.version > PLAN_VERSION {
+            bail!(
+                "Install plan version {} is newer than this Slate understands ({})",
+                self.version,
+                PLAN_VERSION
+            );
+        }
+        if self.version == PLAN_VERSION {
+            return Ok(false);
+        }
+        // Serde already filled version 1 gaps with the defaults they were installed with.
+        self.version = PLAN_VERSION;
+        Ok(true)
+    }
+}
+
+/// Install settings supplied on the command line rather than through the TUI form.
+#[derive(Debug, Clone)]
+pub struct InstallOptions {
+    pub subvolumes: Vec<Subvolume>,
+    /// Pre-filled into the form; still editable before review.
+    pub hostname: Option<String>,
+    pub username: Option<String>,
+    pub preserve_esp: bool,
+    pub btrfs_options: String,
+    pub boot_title: String,
+    pub boot_timeout: u32,
+}
+
+impl Default for InstallOptions {
+    fn default() -> Self {
+        Self {
+            subvolumes: default_subvolumes(),
+            hostname: None,
+            username: None,
+            preserve_esp: false,
+            btrfs_options: default_btrfs_options(),
+            boot_title: default_boot_title(),
+            boot_timeout: default_boot_timeout(),
+        }
+    }
+}
+
+/// A single RFC 1123 label: letters, digits and inner hyphens, at most 63 characters.
+pub fn validate_hostname(hostname: &str) -> Result<()> {
+    if hostname.is_empty() || hostname.len() > 63 {
+        bail!("Hostname must be 1-63 characters: {}", hostname);
+    }
+    if hostname.starts_with('-') || hostname.ends_with('-') {
+        bail!("Hostname cannot start or end with '-': {}", hostname);
+    }
+    if !hostname
+        .chars()
+        .all(|ch| ch.is_ascii_alphanumeric() || ch == '-')
+    {
+        bail!(
+            "Hostname may only contain letters, digits and '-': {}",
+            hostname
+        );
+    }
+    Ok(())
+}
+
+/// Same rules as useradd's default NAME_REGEX, capped at 32 characters.
+pub fn validate_username(username: &str) -> Result<()> {
+    if username.is_empty() || username.len() > 32 {
+        bail!("Username must be 1-32 characters: {}", username);
+    }
+    let mut chars = username.chars();
+    let first = chars.next().unwrap_or_default();
+    if !(first.is_ascii_lowercase() || first == '_') {
+        bail!(
+            "Username must start with a lowercase letter or '_': {}",
+            username
+        );
+    }
+    if !chars.all(|ch| ch.is_ascii_lowercase() || ch.is_ascii_digit() || ch == '_' || ch == '-') {
+        bail!(
+            "Username may only contain lowercase letters, digits, '_' and '-': {}",
+            username
+        );
+    }
+    if username == "root" {
+        bail!("Username cannot be root");
+    }
+    Ok(())
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct Subvolume {
+    pub name: String,
+    pub mountpoint: String,
+}
+
+impl Subvolume {
+    fn new(name: &str, mountpoint: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            mountpoint: mountpoint.to_string(),
+        }
+    }
+}
+
+pub fn validate_boot_title(title: &str) -> Result<()> {
+    if title.trim().is_empty() || title.chars().any(char::is_control) {
+        bail!("Boot entry title must be non-empty text on one line");
+    }
+    Ok(())
+}
+
+/// A locale.gen name such as `de_DE.UTF-8` or `sr_RS@latin`.
+pub fn validate_locale(locale: &str) -> Result<()> {
+    if locale.is_empty()
+        || !locale
+            .chars()
+            .all(|ch| ch.is_ascii_alphanumeric() || matches!(ch, '_' | '.' | '-' | '@'))
+    {
+        bail!("Invalid locale '{}'", locale);
+    }
+    Ok(())
+}
+
+pub fn default_locale() -> String {
+    DEFAULT_LOCALE.to_string()
+}
+
+pub fn default_boot_title() -> String {
+    "Slate".to_string()
+}
+
+pub fn default_boot_timeout() -> u32 {
+    3
+}
+
+pub fn default_btrfs_options() -> String {
+    BTRFS_MOUNT_OPTIONS.to_string()
+}
+
+fn default_autologin() -> bool {
+    true
+}
+
+fn legacy_plan_version() -> u32 {
+    1
+}
+
+pub fn default_subvolumes() -> Vec<Subvolume> {
+    vec![
+        Subvolume::new("@", "/"),
+        Subvolume::new("@home", "/home"),
+        Subvolume::new("@log", "/var/log"),
+        Subvolume::new("@pkg", "/var/cache/pacman/pkg"),
+        Subvolume::new("@snapshots", "/.snapshots"),
+    ]
+}
+
+/// Parse a subvolume layout such as `@=/,@home=/home,@var=/var`.
+pub fn parse_subvolumes(spec: &str) -> Result<Vec<Subvolume>> {
+    let mut subvolumes = Vec::new();
+    for pair in spec
+        .split(',')
+        .map(str::trim)
+        .filter(|pair| !pair.is_empty())
+    {
+        let (name, mountpoint) = pair.split_once('=').ok_or_else(|| {
+            anyhow!(
+                "Invalid subvolume entry '{}': expected name=mountpoint",
+                pair
+            )
+        })?;
+        subvolumes.push(Subvolume::new(name.trim(), mountpoint.trim()));
+    }
+    validate_subvolumes(&subvolumes)?;
+    Ok(subvolumes)
+}
+
+fn root_subvolume(subvolumes: &[Subvolume]) -> Result<&Subvolume> {
+    subvolumes
+        .iter()
+        .find(|subvol| subvol.mountpoint == "/")
+        .ok_or_else(|| anyhow!("Subvolume layout has no root (/) subvolume"))
+}
+
+fn validate_subvolumes(subvolumes: &[Subvolume]) -> Result<()> {
+    let mut names = HashSet::new();
+    let mut mountpoints = HashSet::new();
+    for subvol in subvolumes {
+        if subvol.name.is_empty()
+            || subvol.name == "."
+            || subvol.name == ".."
+            || subvol
+                .name
+                .chars()
+                .any(|ch| ch == '/' || ch == ',' || ch.is_whitespace())
+        {
+            bail!("Invalid subvolume name '{}'", subvol.name);
+        }
+        if !subvol.mountpoint.starts_with('/')
+            || subvol.mountpoint.chars().any(char::is_whitespace)
+            || (subvol.mountpoint != "/"
+                && subvol.mountpoint[1..]
+                    .split('/')
+                    .any(|part| part.is_empty() || part == "." || part == ".."))
+        {
+            bail!(
+                "Invalid mountpoint '{}' for subvolume {}",
+                subvol.mountpoint,
+                subvol.name
+            );
+        }
+        if subvol.mountpoint == "/boot" || subvol.mountpoint.starts_with("/boot/") {
+            bail!("/boot is reserved for the EFI partition");
+        }
+        if !names.insert(subvol.name.as_str()) {
+            bail!("Duplicate subvolume name '{}'", subvol.name);
+        }
+        if !mountpoints.insert(subvol.mountpoint.as_str()) {
+            bail!("Duplicate subvolume mountpoint '{}'", subvol.mountpoint);
+        }
+    }
+    if !mountpoints.contains("/") {
+        bail!("Subvolume layout must mount one subvolume at /");
+    }
+    Ok(())
+}
+
+/// `subvol`/`subvolid` are rejected because the layout decides them per mount.
+pub fn validate_btrfs_options(options: &str) -> Result<()> {
+    for option in options.split(',') {
+        let (key, value) = match option.split_once('=') {
+            Some((key, value)) => (key, Some(value)),
+            None => (option, None),
+        };
+        let valid = match (key, value) {
+            ("subvol" | "subvolid", _) => {
+                bail!("'{}' is set per subvolume and cannot be overridden", key)
+            }
+            (
+                "rw" | "noatime" | "relatime" | "lazytime" | "ssd" | "nossd" | "ssd_spread"
+                | "autodefrag" | "noautodefrag" | "discard" | "nodiscard" | "nospace_cache"
+                | "nodatacow" | "nodatasum",
+                None,
+            ) => true,
+            ("discard", Some(mode)) => matches!(mode, "sync" | "async"),
+            ("space_cache", Some(version)) => matches!(version, "v1" | "v2"),
+            ("commit", Some(seconds)) => seconds.parse::<u32>().is_ok_and(|value| value > 0),
+            ("compress" | "compress-force", Some(algo)) => valid_btrfs_compression(algo),
+            _ => false,
+        };
+        if !valid {
+            bail!("Unsupported btrfs mount option '{}'", option);
+        }
+    }
+    Ok(())
+}
+
+fn valid_btrfs_compression(algo: &str) -> bool {
+    match algo.split_once(':') {
+        None => matches!(algo, "zstd" | "zlib" | "lzo" | "no" | "none"),
+        Some(("zstd", level)) => level
+            .parse::<u32>()
+            .is_ok_and(|level| (1..=15).contains(&level)),
+        Some(("zlib", level)) => level
+            .parse::<u32>()
+            .is_ok_and(|level| (1..=9).contains(&level)),
+        Some(_) => false,
+    }
 }
 
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
@@ -85,6 +404,13 @@ impl StageId {
             StageId::Finalize => "Finalize",
         }
     }
+
+    pub fn error_class(self) -> fn(anyhow::Error) -> SlateError {
+        match self {
+            StageId::PrepareDisk => SlateError::Device,
+            _ => SlateError::Install,
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
@@ -152,28 +478,132 @@ pub fn run_install(plan: InstallPlan, sink: EventSink) {
 }
 
 pub fn run_stage_apply() -> Result<()> {
-    let plan = read_plan_from(Path::new("/etc/slate/install-plan.json"))?;
+    persist_migrated_plan(Path::new(INSTALLED_PLAN_PATH))?;
+    let plan = read_plan_from(Path::new(INSTALLED_PLAN_PATH))?;
     let mut ctx = ChrootContext::new(plan);
     ctx.execute()
 }
 
-pub fn repair() -> Result<()> {
+/// With `check_only`, nothing is changed and any needed repair fails with [`SlateError::Drift`].
+pub fn repair(check_only: bool) -> Result<()> {
     if !nix::unistd::Uid::effective().is_root() {
-        bail!("`slate repair` must be run as root, preferably via sudo");
+        return Err(SlateError::Environment(anyhow!(
+            "`slate repair` must be run as root, preferably via sudo"
+        ))
+        .into());
     }
 
-    let target = RepairTarget::resolve()?;
-    let mut ctx = RepairContext::new(target)?;
+    // Repair also runs on systems with a damaged plan; upgrading it is best effort.
+    if !check_only && Path::new(INSTALLED_PLAN_PATH).exists() {
+        if let Err(err) = persist_migrated_plan(Path::new(INSTALLED_PLAN_PATH)) {
+            println!(
+                "Warning: could not upgrade {}: {:#}",
+                INSTALLED_PLAN_PATH, err
+            );
+        }
+    }
+    let target = RepairTarget::resolve().classify(SlateError::Environment)?;
+    let mut ctx = RepairContext::new(target, check_only)?;
     ctx.run()
 }
 
+pub fn resume_install(disk: &str) -> Result<()> {
+    if !nix::unistd::Uid::effective().is_root() {
+        return Err(SlateError::Environment(anyhow!(
+            "`slate install --resume` must be run as root"
+        ))
+        .into());
+    }
+
+    let (tx, rx) = mpsc::channel();
+    let printer = thread::spawn(move || {
+        for event in rx {
+            if let InstallEvent::Log(line) = event {
+                println!("{}", line);
+            }
+        }
+    });
+    let result = (|| -> Result<()> {
+        let mut ctx =
+            InstallContext::reopen(disk, EventSink::new(tx)).classify(SlateError::Device)?;
+        ctx.execute_resume()
+    })();
+    let _ = printer.join();
+    result
+}
+
+/// Find the Slate install below a mounted top-level btrfs volume.
+fn find_existing_install(top_level: &Path) -> Result<(InstallPlan, Checkpoint)> {
+    let mut entries = fs::read_dir(top_level)?
+        .map(|entry| entry.map(|entry| entry.file_name()))
+        .collect::<std::io::Result<Vec<_>>>()?;
+    entries.sort();
+    for name in entries {
+        let plan_path = top_level.join(&name).join("etc/slate/install-plan.json");
+        if !plan_path.exists() {
+            continue;
+        }
+
+        let plan = read_plan_from(&plan_path)?;
+        // Snapshots and copies carry a plan too; only the plan's own root counts.
+        let root = root_subvolume(&plan.subvolumes)?;
+        if name.to_str() != Some(root.name.as_str()) {
+            continue;
+        }
+        for subvol in &plan.subvolumes {
+            if !top_level.join(&subvol.name).is_dir() {
+                bail!(
+                    "Existing layout is missing subvolume {}; refusing to recreate it",
+                    subvol.name
+                );
+            }
+        }
+
+        let checkpoint_path = top_level.join(&root.name).join("etc/slate/checkpoint.json");
+        let checkpoint: Checkpoint = fs::read_to_string(checkpoint_path)
+            .ok()
+            .and_then(|raw| serde_json::from_str(&raw).ok())
+            .unwrap_or_default();
+        if !checkpoint.completed_stages.contains(&StageId::Bootstrap) {
+            bail!("Bootstrap never completed on this disk; run a full install instead");
+        }
+        return Ok((plan, checkpoint));
+    }
+
+    bail!("No Slate install plan found on this disk; nothing to resume")
+}
+
+/// Upgrades the plan in memory only, so it is safe on read-only mounts.
 pub fn read_plan_from(path: &Path) -> Result<InstallPlan> {
+    parse_plan(path).map(|(plan, _)| plan)
+}
+
+/// No backup is kept, since the plan holds the user's password.
+fn persist_migrated_plan(path: &Path) -> Result<()> {
+    let (plan, migrated) = parse_plan(path)?;
+    if migrated {
+        println!(
+            "Migrated {} to install plan version {}",
+            path.display(),
+            PLAN_VERSION
+        );
+        privileged::write(
+            path,
+            serde_json::to_vec_pretty(&plan)?,
+            WriteOptions::default(),
+        )?;
+    }
+    Ok(())
+}
+
+fn parse_plan(path: &Path) -> Result<(InstallPlan, bool)> {
     let raw =
         fs::read_to_string(path).with_context(|| format!("Failed to read {}", path.display()))?;
-    let plan: InstallPlan = serde_json::from_str(&raw)
+    let mut plan: InstallPlan = serde_json::from_str(&raw)
         .with_context(|| format!("Failed to parse {}", path.display()))?;
+    let migrated = plan.migrate()?;
     plan.validate()?;
-    Ok(plan)
+    Ok((plan, migrated))
 }
 
 fn persist_host_plan(plan: &InstallPlan) -> Result<()> {
@@ -211,6 +641,64 @@ impl InstallContext {
         Ok(())
     }
 
+    /// Remount an existing Slate install on `disk` without touching its partitions.
+    fn reopen(disk: &str, sink: EventSink) -> Result<Self> {
+        let efi = system::partition_path(disk, 1);
+        let root = system::partition_path(disk, 2);
+        for part in [&efi, &root] {
+            if !Path::new(part).exists() {
+                bail!(
+                    "{} not found; --resume only reopens a disk Slate already partitioned",
+                    part
+                );
+            }
+        }
+
+        let mut mounts = MountTable::default();
+        let runner = CommandRunner::new(&sink, None);
+        runner.run(
+            "umount",
+            &["-R", TARGET_ROOT],
+            Some(Duration::from_secs(20)),
+            true,
+        )?;
+        fs::create_dir_all(TARGET_ROOT)?;
+        mounts.mount(&runner, &root, TARGET_ROOT, &["-o", "ro,subvolid=5"])?;
+        let existing = find_existing_install(Path::new(TARGET_ROOT));
+        mounts.unmount(&runner, TARGET_ROOT)?;
+        let (mut plan, checkpoint) = existing?;
+
+        plan.disk = disk.to_string();
+        mounts.mount_layout(&runner, &plan.subvolumes, &plan.btrfs_options, &root, &efi)?;
+        Ok(Self {
+            plan,
+            sink,
+            checkpoint,
+            current_stage: None,
+            mounts,
+        })
+    }
+
+    fn execute_resume(&mut self) -> Result<()> {
+        self.sink
+            .log(format!("Resuming install on {}", self.plan.disk));
+        self.checkpoint.completed_stages.retain(|stage| {
+            matches!(
+                stage,
+                StageId::Collect | StageId::PrepareDisk | StageId::Bootstrap
+            )
+        });
+        // Ship this binary so the chroot stage runs the same code as the host.
+        self.persist_target_plan()?;
+        let runner = CommandRunner::new(&self.sink, Some(StageId::Bootstrap));
+        self.install_slate_binary(&runner)?;
+
+        self.run_stage(StageId::StageApply, |ctx| ctx.stage_apply())?;
+        self.run_stage(StageId::Verify, |ctx| ctx.verify())?;
+        self.run_stage(StageId::Finalize, |ctx| ctx.finalize())?;
+        Ok(())
+    }
+
     fn run_stage<F>(&mut self, stage: StageId, f: F) -> Result<()>
     where
         F: FnOnce(&mut Self) -> Result<()>,
@@ -235,7 +723,7 @@ impl InstallContext {
                 self.checkpoint.last_error = Some(rendered.clone());
                 self.persist_checkpoint()?;
                 self.sink.failed(Some(stage), rendered.clone());
-                Err(anyhow!(rendered))
+                Err(stage.error_class()(anyhow!(rendered)).into())
             }
         }
     }
@@ -268,16 +756,10 @@ impl InstallContext {
         for tool in tools {
             require_command(tool)?;
         }
+        // Fail on a broken manifest before the disk is touched.
+        PackageManifest::load()?;
 
-        let status = Command::new("curl")
-            .args(["-I", "--connect-timeout", "10", "https://archlinux.org"])
-            .stdout(Stdio::null())
-            .stderr(Stdio::null())
-            .status()
-            .context("Failed to run curl network check")?;
-        if !status.success() {
-            bail!("Network check failed");
-        }
+        system::network::require_online()?;
 
         self.sink.log(format!("Target disk: {}", self.plan.disk));
         self.sink.log(format!(
@@ -297,24 +779,56 @@ impl InstallContext {
             Some(Duration::from_secs(20)),
             true,
         )?;
-        runner.run(
-            "sgdisk",
-            &["--zap-all", disk],
-            Some(Duration::from_secs(20)),
-            false,
-        )?;
-        runner.run(
-            "sgdisk",
-            &["-o", disk],
-            Some(Duration::from_secs(20)),
-            false,
-        )?;
-        runner.run(
-            "sgdisk",
-            &["-n", "1:0:+1G", "-t", "1:ef00", "-c", "1:EFI", disk],
-            Some(Duration::from_secs(20)),
-            false,
-        )?;
+        let efi = system::partition_path(disk, 1);
+        let root = system::partition_path(disk, 2);
+
+        if self.plan.preserve_esp {
+            let probe = probe_partition(&efi)?;
+            check_preserved_esp(&efi, &probe)?;
+            // A shared ESP can be big enough yet full of another OS's kernels.
+            let available = {
+                let mut check_mount = MountTable::default();
+                check_mount.mount(&runner, &efi, ESP_CHECK_MOUNT, &["-o", "ro"])?;
+                check_esp_contents(&efi, Path::new(ESP_CHECK_MOUNT))?;
+                system::available_space(Path::new(ESP_CHECK_MOUNT))?
+            };
+            let _ = fs::remove_dir(ESP_CHECK_MOUNT);
+            check_esp_free_space(&efi, available)?;
+            self.sink.log(format!(
+                "WARNING: keeping existing ESP {}; every other partition on {} will be deleted",
+                efi, disk
+            ));
+            for number in system::partition_numbers(disk)? {
+                if number == 1 {
+                    continue;
+                }
+                runner.run(
+                    "sgdisk",
+                    &["-d", &number.to_string(), disk],
+                    Some(Duration::from_secs(20)),
+                    false,
+                )?;
+            }
+        } else {
+            runner.run(
+                "sgdisk",
+                &["--zap-all", disk],
+                Some(Duration::from_secs(20)),
+                false,
+            )?;
+            runner.run(
+                "sgdisk",
+                &["-o", disk],
+                Some(Duration::from_secs(20)),
+                false,
+            )?;
+            runner.run(
+                "sgdisk",
+                &["-n", "1:0:+1G", "-t", "1:ef00", "-c", "1:EFI", disk],
+                Some(Duration::from_secs(20)),
+                false,
+            )?;
+        }
         runner.run(
             "sgdisk",
             &["-n", "2:0:0", "-t", "2:8300", "-c", "2:ROOT", disk],
@@ -322,17 +836,17 @@ impl InstallContext {
             false,
         )?;
 
-        let efi = system::partition_path(disk, 1);
-        let root = system::partition_path(disk, 2);
         wait_for_path(&efi, Duration::from_secs(15))?;
         wait_for_path(&root, Duration::from_secs(15))?;
 
-        runner.run(
-            "mkfs.vfat",
-            &["-F", "32", "-n", "SLATE_EFI", &efi],
-            Some(Duration::from_secs(30)),
-            false,
-        )?;
+        if !self.plan.preserve_esp {
+            runner.run(
+                "mkfs.vfat",
+                &["-F", "32", "-n", "SLATE_EFI", &efi],
+                Some(Duration::from_secs(30)),
+                false,
+            )?;
+        }
         runner.run(
             "mkfs.btrfs",
             &["-f", "-L", "SLATE_ROOT", &root],
@@ -345,90 +859,40 @@ impl InstallContext {
             &runner,
             &root,
             TARGET_ROOT,
-            &["-o", "rw,noatime,compress=zstd,space_cache=v2"],
+            &["-o", &self.plan.btrfs_options],
         )?;
 
-        for subvol in ["@", "@home", "@log", "@pkg", "@snapshots"] {
+        for subvol in &self.plan.subvolumes {
             runner.run(
                 "btrfs",
-                &["subvolume", "create", &format!("{TARGET_ROOT}/{subvol}")],
+                &[
+                    "subvolume",
+                    "create",
+                    &format!("{TARGET_ROOT}/{}", subvol.name),
+                ],
                 Some(Duration::from_secs(30)),
                 false,
             )?;
         }
 
         self.mounts.unmount(&runner, TARGET_ROOT)?;
-        self.mounts.mount(
-            &runner,
-            &root,
-            TARGET_ROOT,
-            &["-o", "rw,noatime,compress=zstd,space_cache=v2,subvol=@"],
-        )?;
-
-        for dir in [
-            "/mnt/home",
-            "/mnt/var/log",
-            "/mnt/var/cache/pacman/pkg",
-            "/mnt/.snapshots",
-            "/mnt/boot",
-            "/mnt/etc/slate",
-        ] {
-            fs::create_dir_all(dir)?;
-        }
-
-        self.mounts.mount(
-            &runner,
-            &root,
-            "/mnt/home",
-            &["-o", "rw,noatime,compress=zstd,space_cache=v2,subvol=@home"],
-        )?;
-        self.mounts.mount(
-            &runner,
-            &root,
-            "/mnt/var/log",
-            &["-o", "rw,noatime,compress=zstd,space_cache=v2,subvol=@log"],
-        )?;
-        self.mounts.mount(
+        self.mounts.mount_layout(
             &runner,
+            &self.plan.subvolumes,
+            &self.plan.btrfs_options,
             &root,
-            "/mnt/var/cache/pacman/pkg",
-            &["-o", "rw,noatime,compress=zstd,space_cache=v2,subvol=@pkg"],
+            &efi,
         )?;
-        self.mounts.mount(
-            &runner,
-            &root,
-            "/mnt/.snapshots",
-            &[
-                "-o",
-                "rw,noatime,compress=zstd,space_cache=v2,subvol=@snapshots",
-            ],
-        )?;
-        self.mounts.mount(&runner, &efi, "/mnt/boot", &[])?;
         Ok(())
     }
 
     fn bootstrap(&mut self) -> Result<()> {
         let runner = CommandRunner::new(&self.sink, Some(StageId::Bootstrap));
-        let packages = [
-            "base",
-            "linux",
-            "linux-firmware",
-            "base-devel",
-            "btrfs-progs",
-            "sudo",
-            "networkmanager",
-            "systemd",
-            "curl",
-            "zsh",
-            "intel-ucode",
-            "amd-ucode",
-            "libgit2",
-            "git"
-        ];
+        let manifest = PackageManifest::load()?;
 
         self.sink.log("Bootstrapping base system...");
         let mut args = vec!["-K", TARGET_ROOT];
-        args.extend(packages);
+        args.extend(manifest.base.iter().map(String::as_str));
         runner.run("pacstrap", &args, Some(Duration::from_secs(1800)), false)?;
 
         let output = Command::new("genfstab")
@@ -438,13 +902,40 @@ impl InstallContext {
         if !output.status.success() {
             bail!("genfstab failed");
         }
-        fs::write("/mnt/etc/fstab", output.stdout).context("Failed to write fstab")?;
+        privileged::write("/mnt/etc/fstab", output.stdout, WriteOptions::default())
+            .context("Failed to write fstab")?;
+
+        self.persist_target_plan()?;
+        self.persist_checkpoint()?;
+        self.install_slate_binary(&runner)?;
+        runner.run(
+            "curl",
+            &["-L", "--fail", AX_BINARY_URL, "-o", "/mnt/usr/local/bin/ax"],
+            Some(Duration::from_secs(120)),
+            false,
+        )?;
+        runner.run(
+            "chmod",
+            &["+x", "/mnt/usr/local/bin/ax"],
+            Some(Duration::from_secs(10)),
+            false,
+        )?;
+        Ok(())
+    }
 
+    fn persist_target_plan(&self) -> Result<()> {
         fs::create_dir_all("/mnt/etc/slate")?;
         fs::write(TARGET_PLAN_PATH, serde_json::to_vec_pretty(&self.plan)?)
             .context("Failed to write target install plan")?;
-        self.persist_checkpoint()?;
+        // Later stages and repairs must install the same groups as bootstrap.
+        if Path::new(PACKAGE_MANIFEST_PATH).exists() {
+            fs::copy(PACKAGE_MANIFEST_PATH, "/mnt/etc/slate/packages.toml")
+                .context("Failed to copy package manifest into target")?;
+        }
+        Ok(())
+    }
 
+    fn install_slate_binary(&self, runner: &CommandRunner<'_>) -> Result<()> {
         let current_exe =
             std::env::current_exe().context("Failed to resolve current executable")?;
         fs::create_dir_all("/mnt/usr/local/bin")?;
@@ -456,18 +947,6 @@ impl InstallContext {
             Some(Duration::from_secs(10)),
             false,
         )?;
-        runner.run(
-            "curl",
-            &["-L", "--fail", AX_BINARY_URL, "-o", "/mnt/usr/local/bin/ax"],
-            Some(Duration::from_secs(120)),
-            false,
-        )?;
-        runner.run(
-            "chmod",
-            &["+x", "/mnt/usr/local/bin/ax"],
-            Some(Duration::from_secs(10)),
-            false,
-        )?;
         Ok(())
     }
 
@@ -500,6 +979,21 @@ impl InstallContext {
         if !Path::new(&format!("/mnt/home/{username}")).exists() {
             bail!("Verification failed: missing user home for {}", username);
         }
+
+        if let Err(err) = check_mkinitcpio_conf(Path::new("/mnt/etc/mkinitcpio.conf")) {
+            bail!("Verification failed: {:#}", err);
+        }
+
+        let fstab = fs::read_to_string("/mnt/etc/fstab").context("Failed to read fstab")?;
+        for subvol in &self.plan.subvolumes {
+            if !fstab_mounts(&fstab, &subvol.mountpoint) {
+                bail!(
+                    "Verification failed: fstab has no entry for {} ({})",
+                    subvol.mountpoint,
+                    subvol.name
+                );
+            }
+        }
         Ok(())
     }
 
@@ -547,6 +1041,44 @@ impl MountTable {
         Ok(())
     }
 
+    fn mount_layout(
+        &mut self,
+        runner: &CommandRunner<'_>,
+        subvolumes: &[Subvolume],
+        options: &str,
+        root: &str,
+        efi: &str,
+    ) -> Result<()> {
+        let root_subvol = &root_subvolume(subvolumes)?.name;
+        self.mount(
+            runner,
+            root,
+            TARGET_ROOT,
+            &["-o", &format!("{},subvol={}", options, root_subvol)],
+        )?;
+
+        // Mount parents before children, e.g. /var before /var/log.
+        let mut nested: Vec<&Subvolume> = subvolumes
+            .iter()
+            .filter(|subvol| subvol.mountpoint != "/")
+            .collect();
+        nested.sort_by_key(|subvol| subvol.mountpoint.matches('/').count());
+
+        fs::create_dir_all("/mnt/boot")?;
+        fs::create_dir_all("/mnt/etc/slate")?;
+        for subvol in nested {
+            let target = format!("{TARGET_ROOT}{}", subvol.mountpoint);
+            self.mount(
+                runner,
+                root,
+                &target,
+                &["-o", &format!("{},subvol={}", options, subvol.name)],
+            )?;
+        }
+        self.mount(runner, efi, "/mnt/boot", &[])?;
+        Ok(())
+    }
+
     fn unmount(&mut self, runner: &CommandRunner<'_>, target: &str) -> Result<()> {
         runner.run("umount", &[target], Some(Duration::from_secs(20)), false)?;
         self.targets.retain(|item| item != target);
@@ -692,6 +1224,90 @@ fn sanitize_for_log(input: &str) -> String {
     out.trim().to_string()
 }
 
+fn fstab_mounts(fstab: &str, mountpoint: &str) -> bool {
+    fstab
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.starts_with('#'))
+        .any(|line| line.split_whitespace().nth(1) == Some(mountpoint))
+}
+
+/// Return `lsblk`'s FSTYPE, PARTTYPE and SIZE (bytes) columns for a partition.
+fn probe_partition(part: &str) -> Result<String> {
+    let output = Command::new("lsblk")
+        .args(["-bndo", "FSTYPE,PARTTYPE,SIZE", part])
+        .output()
+        .context("Failed to run lsblk")?;
+    if !output.status.success() {
+        bail!("No partition found at {} to preserve as the ESP", part);
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+fn check_preserved_esp(part: &str, probe: &str) -> Result<()> {
+    let fields: Vec<&str> = probe.split_whitespace().collect();
+    if fields.len() != 3 {
+        bail!("{} is not a formatted EFI system partition", part);
+    }
+    let (fstype, parttype, size) = (fields[0], fields[1], fields[2]);
+    if fstype != "vfat" || !parttype.eq_ignore_ascii_case(ESP_PARTTYPE) {
+        bail!(
+            "{} is not a vfat EFI system partition (found {} {})",
+            part,
+            fstype,
+            parttype
+        );
+    }
+    let size: u64 = size
+        .parse()
+        .with_context(|| format!("Invalid size reported for {}", part))?;
+    if size < MIN_PRESERVED_ESP_BYTES {
+        bail!(
+            "ESP {} is {} MiB; at least {} MiB is needed for the Slate kernel and initramfs",
+            part,
+            size / 1024 / 1024,
+            MIN_PRESERVED_ESP_BYTES / 1024 / 1024
+        );
+    }
+    Ok(())
+}
+
+fn check_esp_contents(part: &str, esp: &Path) -> Result<()> {
+    let mut existing = Vec::new();
+    for entry in fs::read_dir(esp).with_context(|| format!("Failed to read ESP {}", part))? {
+        let name = entry?.file_name().to_string_lossy().into_owned();
+        if name == "vmlinuz-linux"
+            || (name.starts_with("initramfs-linux") && name.ends_with(".img"))
+        {
+            existing.push(name);
+        }
+    }
+    if esp.join("loader/entries/slate.conf").exists() {
+        existing.push("loader/entries/slate.conf".to_string());
+    }
+    if !existing.is_empty() {
+        existing.sort();
+        bail!(
+            "ESP {} already holds {}; Slate would overwrite them. Remove them or install without --preserve-esp",
+            part,
+            existing.join(", ")
+        );
+    }
+    Ok(())
+}
+
+fn check_esp_free_space(part: &str, available: u64) -> Result<()> {
+    if available < MIN_PRESERVED_ESP_FREE_BYTES {
+        bail!(
+            "ESP {} has {} MiB free; at least {} MiB is needed for the Slate kernel and initramfs",
+            part,
+            available / 1024 / 1024,
+            MIN_PRESERVED_ESP_FREE_BYTES / 1024 / 1024
+        );
+    }
+    Ok(())
+}
+
 fn wait_for_path(path: &str, timeout: Duration) -> Result<()> {
     let deadline = Instant::now() + timeout;
     while Instant::now() < deadline {
@@ -719,11 +1335,16 @@ fn require_command(cmd: &str) -> Result<()> {
 
 struct ChrootContext {
     plan: InstallPlan,
+    /// Shell checkout fetched during this run; removed when the stage ends.
+    shell_source: OnceCell<TempPath>,
 }
 
 impl ChrootContext {
     fn new(plan: InstallPlan) -> Self {
-        Self { plan }
+        Self {
+            plan,
+            shell_source: OnceCell::new(),
+        }
     }
 
     fn execute(&mut self) -> Result<()> {
@@ -734,18 +1355,26 @@ impl ChrootContext {
         self.desktop_assets()?;
         self.desktop_finalize()?;
         self.auto_login()?;
+        self.write_summary()?;
         Ok(())
     }
 
+    fn write_summary(&self) -> Result<()> {
+        privileged::write(
+            INSTALL_SUMMARY_PATH,
+            render_install_summary(&self.plan),
+            WriteOptions::default(),
+        )
+    }
+
     fn auto_login(&self) -> Result<()> {
-        let dir = "/etc/systemd/system/getty@tty1.service.d";
-        fs::create_dir_all(dir)?;
-        fs::write(
-            format!("{}/autologin.conf", dir),
-            format!(
-                "[Service]\nExecStart=\nExecStart=-/usr/bin/agetty --autologin {} --noclear %I $TERM\n",
-                self.plan.username
-            ),
+        if !self.plan.autologin {
+            return remove_autologin_override();
+        }
+        privileged::write(
+            AUTOLOGIN_OVERRIDE_PATH,
+            render_autologin_override(&self.plan.username),
+            WriteOptions::default(),
         )?;
         Ok(())
     }
@@ -756,9 +1385,10 @@ impl ChrootContext {
         self.write_hostname()?;
         self.write_locale()?;
         self.write_timezone()?;
-        fs::write(
+        privileged::write(
             "/etc/vconsole.conf",
             format!("KEYMAP={}\n", self.plan.keymap),
+            WriteOptions::default(),
         )?;
         run_simple("systemctl", &["enable", "NetworkManager"])?;
         run_simple("systemctl", &["enable", "systemd-timesyncd"])?;
@@ -794,66 +1424,35 @@ impl ChrootContext {
                 self.plan.password, self.plan.username, self.plan.password
             ),
         )?;
-
-        let sudoers = "/etc/sudoers";
-        let content = fs::read_to_string(sudoers).context("Failed to read sudoers")?;
-        let mut updated = if content.contains("%wheel ALL=(ALL:ALL) ALL") {
-            content
-        } else {
-            content.replace("# %wheel ALL=(ALL:ALL) ALL", "%wheel ALL=(ALL:ALL) ALL")
-        };
-        if !updated.contains("@includedir /etc/sudoers.d")
-            && !updated.contains("#includedir /etc/sudoers.d")
-        {
-            updated.push_str("\n@includedir /etc/sudoers.d\n");
-        } else {
-            updated = updated.replace("#includedir /etc/sudoers.d", "@includedir /etc/sudoers.d");
-        }
-        fs::write(sudoers, updated)?;
-        fs::create_dir_all("/etc/sudoers.d")?;
-        fs::write(
-            format!("/etc/sudoers.d/10-{}", self.plan.username),
-            format!("{} ALL=(ALL:ALL) ALL\n", self.plan.username),
-        )?;
-        fs::set_permissions(
-            format!("/etc/sudoers.d/10-{}", self.plan.username),
-            fs::Permissions::from_mode(0o440),
-        )?;
-        Ok(())
+        configure_sudoers(&self.plan.username)
     }
 
     fn boot_config(&self) -> Result<()> {
-        run_simple("bootctl", &["install"])?;
         let root_device = system::find_mount_source(TARGET_ROOT)?
             .unwrap_or_else(|| system::partition_path(&self.plan.disk, 2));
         let root_uuid = system::get_uuid(&root_device)?;
-        fs::create_dir_all("/boot/loader/entries")?;
-        fs::write(
-            "/boot/loader/loader.conf",
-            "default slate.conf\ntimeout 3\nconsole-mode max\n",
-        )?;
-        fs::write(
-            "/boot/loader/entries/slate.conf",
-            format!(
-                "title Slate\nlinux /vmlinuz-linux\ninitrd /intel-ucode.img\ninitrd /amd-ucode.img\ninitrd /initramfs-linux.img\noptions root=UUID={} rw rootflags=subvol=@\n",
-                root_uuid
-            ),
-        )?;
-        Ok(())
+        let root_subvol = &root_subvolume(&self.plan.subvolumes)?.name;
+        install_bootloader(
+            &root_uuid,
+            root_subvol,
+            &self.plan.boot_title,
+            self.plan.boot_timeout,
+            self.plan.preserve_esp,
+        )
     }
 
     fn desktop_packages(&self) -> Result<()> {
-        self.ensure_shell_source()?;
+        ensure_shell_source(&self.shell_source)?;
         let requirements =
             parse_requirements_file(&Path::new(SHELL_REPO_DIR).join("requirements.txt"))
                 .context("Failed to parse shell requirements")?;
-        let packages = merged_package_plan(&requirements);
+        let packages = merged_package_plan(&PackageManifest::load()?, &requirements);
         install_packages_with_ax(&self.plan.username, &self.target_home(), &packages)?;
         Ok(())
     }
 
     fn desktop_assets(&self) -> Result<()> {
-        self.ensure_shell_source()?;
+        ensure_shell_source(&self.shell_source)?;
 
         let user_home = PathBuf::from(format!("/home/{}", self.plan.username));
         let config_dst = user_home.join(".config");
@@ -863,11 +1462,12 @@ impl ChrootContext {
         copy_dir_contents(
             Path::new(SHELL_REPO_DIR).join(".config").as_path(),
             &config_dst,
+            &user_home,
         )?;
         let local_src = Path::new(SHELL_REPO_DIR).join(".local");
         if local_src.exists() {
             fs::create_dir_all(&local_dst)?;
-            copy_dir_contents(&local_src, &local_dst)?;
+            copy_dir_contents(&local_src, &local_dst, &user_home)?;
         }
 
         apply_shell_overrides(&self.plan, &user_home)?;
@@ -890,17 +1490,9 @@ impl ChrootContext {
                 user_home.to_string_lossy().as_ref(),
             ],
         )?;
-        let _ = fs::remove_dir_all(SHELL_REPO_DIR);
         Ok(())
     }
 
-    fn ensure_shell_source(&self) -> Result<()> {
-        if Path::new(SHELL_REPO_DIR).exists() {
-            return Ok(());
-        }
-        fetch_repo_archive(SHELL_ARCHIVE_URL, Path::new(SHELL_REPO_DIR))
-    }
-
     fn desktop_finalize(&self) -> Result<()> {
         let user_home = self.target_home();
         fs::write(
@@ -923,8 +1515,11 @@ impl ChrootContext {
     }
 
     fn write_hostname(&self) -> Result<()> {
-        fs::write("/etc/hostname", format!("{}\n", self.plan.hostname))?;
-        Ok(())
+        privileged::write(
+            "/etc/hostname",
+            format!("{}\n", self.plan.hostname),
+            WriteOptions::default(),
+        )
     }
 
     fn target_home(&self) -> PathBuf {
@@ -932,13 +1527,7 @@ impl ChrootContext {
     }
 
     fn write_locale(&self) -> Result<()> {
-        let locale_gen = "/etc/locale.gen";
-        let content = fs::read_to_string(locale_gen).context("Failed to read locale.gen")?;
-        let updated = content.replace("#en_US.UTF-8 UTF-8", "en_US.UTF-8 UTF-8");
-        fs::write(locale_gen, updated)?;
-        fs::write("/etc/locale.conf", "LANG=en_US.UTF-8\n")?;
-        run_simple("locale-gen", &[])?;
-        Ok(())
+        write_locale_static(&self.plan.locale)
     }
 
     fn write_timezone(&self) -> Result<()> {
@@ -962,26 +1551,18 @@ struct RepairTarget {
     hostname: String,
     keymap: String,
     timezone: String,
+    locale: String,
     git_name: String,
     git_email: String,
+    boot_title: String,
+    boot_timeout: u32,
+    preserve_esp: bool,
 }
 
 impl RepairTarget {
     fn resolve() -> Result<Self> {
-        let username = std::env::var("SUDO_USER")
-            .ok()
-            .filter(|value| !value.trim().is_empty() && value != "root")
-            .or_else(|| {
-                std::env::var("USER")
-                    .ok()
-                    .filter(|value| !value.trim().is_empty() && value != "root")
-            })
-            .ok_or_else(|| anyhow!("Unable to resolve current non-root user"))?;
-
-        let user = nix::unistd::User::from_name(&username)
-            .context("Failed to query target user")?
-            .ok_or_else(|| anyhow!("Target user does not exist: {}", username))?;
-
+        let user = system::real_user()?;
+        let username = user.name;
         let home = user.dir;
         let hostname = fs::read_to_string("/etc/hostname")
             .unwrap_or_else(|_| "slate".to_string())
@@ -990,6 +1571,17 @@ impl RepairTarget {
         let keymap = detect_keymap().unwrap_or_else(|| "us".to_string());
         let timezone = detect_timezone().unwrap_or_else(|| "UTC".to_string());
         let (git_name, git_email) = detect_git_identity(&home).unwrap_or_default();
+        let installed: Option<InstallPlan> = fs::read_to_string(INSTALLED_PLAN_PATH)
+            .ok()
+            .and_then(|raw| serde_json::from_str(&raw).ok());
+        let locale = installed
+            .as_ref()
+            .map(|plan| plan.locale.clone())
+            .or_else(detect_locale)
+            .unwrap_or_else(default_locale);
+        let (boot_title, boot_timeout, preserve_esp) = installed
+            .map(|plan| (plan.boot_title, plan.boot_timeout, plan.preserve_esp))
+            .unwrap_or_else(|| (default_boot_title(), default_boot_timeout(), false));
 
         Ok(Self {
             username,
@@ -997,8 +1589,12 @@ impl RepairTarget {
             hostname,
             keymap,
             timezone,
+            locale,
             git_name,
             git_email,
+            boot_title,
+            boot_timeout,
+            preserve_esp,
         })
     }
 
@@ -1013,29 +1609,46 @@ impl RepairTarget {
             git_name: self.git_name.clone(),
             git_email: self.git_email.clone(),
             desktop_profile: "Slate".to_string(),
+            locale: self.locale.clone(),
+            subvolumes: default_subvolumes(),
+            autologin: Path::new(AUTOLOGIN_OVERRIDE_PATH).exists(),
+            preserve_esp: self.preserve_esp,
+            btrfs_options: default_btrfs_options(),
+            boot_title: self.boot_title.clone(),
+            boot_timeout: self.boot_timeout,
+            version: PLAN_VERSION,
         }
     }
 }
 
 struct RepairContext {
     target: RepairTarget,
+    check_only: bool,
     applied: Vec<&'static str>,
     skipped: Vec<&'static str>,
     failed: Vec<String>,
+    /// Shell checkout fetched during this run; removed when repair returns.
+    shell_source: OnceCell<TempPath>,
 }
 
 impl RepairContext {
-    fn new(target: RepairTarget) -> Result<Self> {
+    fn new(target: RepairTarget, check_only: bool) -> Result<Self> {
         Ok(Self {
             target,
+            check_only,
             applied: Vec::new(),
             skipped: Vec::new(),
             failed: Vec::new(),
+            shell_source: OnceCell::new(),
         })
     }
 
     fn run(&mut self) -> Result<()> {
-        println!("Slate repair");
+        if self.check_only {
+            println!("Slate repair (check only, no changes)");
+        } else {
+            println!("Slate repair");
+        }
         println!("Target user: {}", self.target.username);
         println!("Home: {}", self.target.home.display());
         println!();
@@ -1050,6 +1663,19 @@ impl RepairContext {
         self.run_group("system", system, Self::apply_system);
         let boot = self.inspect_boot()?;
         self.run_group("boot", boot, Self::apply_boot);
+        let permissions = self.inspect_permissions()?;
+        self.run_group("permissions", permissions, Self::apply_permissions);
+
+        if self.check_only {
+            println!();
+            if self.skipped.is_empty() {
+                println!("No repair needed");
+                return Ok(());
+            }
+            return Err(
+                SlateError::Drift(anyhow!("Repair needed: {}", self.skipped.join(", "))).into(),
+            );
+        }
 
         println!();
         println!("Repair summary");
@@ -1100,7 +1726,9 @@ impl RepairContext {
         for issue in &issues {
             println!("  - {}", issue);
         }
-        if prompt_yes_no("Apply this group? [y/N] ").unwrap_or(false) {
+        if self.check_only {
+            self.skipped.push(name);
+        } else if prompt_yes_no("Apply this group? [y/N] ").unwrap_or(false) {
             match apply(self) {
                 Ok(()) => {
                     println!("[done] {}", name);
@@ -1120,11 +1748,11 @@ impl RepairContext {
 
     fn inspect_packages(&self) -> Result<Vec<String>> {
         let mut issues = Vec::new();
-        self.ensure_shell_source()?;
+        ensure_shell_source(&self.shell_source)?;
         let requirements =
             parse_requirements_file(&Path::new(SHELL_REPO_DIR).join("requirements.txt"))
                 .context("Failed to inspect shell requirements")?;
-        let packages = merged_package_plan(&requirements);
+        let packages = merged_package_plan(&PackageManifest::load()?, &requirements);
         let missing = packages
             .iter()
             .filter(|pkg| !package_installed(pkg))
@@ -1145,7 +1773,7 @@ impl RepairContext {
     }
 
     fn inspect_shell(&self) -> Result<Vec<String>> {
-        self.ensure_shell_source()?;
+        ensure_shell_source(&self.shell_source)?;
         let mut issues = Vec::new();
         let config_src = Path::new(SHELL_REPO_DIR).join(".config");
         let local_src = Path::new(SHELL_REPO_DIR).join(".local");
@@ -1217,8 +1845,11 @@ impl RepairContext {
             }
         }
         let locale = fs::read_to_string("/etc/locale.conf").unwrap_or_default();
-        if !locale.contains("LANG=en_US.UTF-8") {
-            issues.push("Locale is not set to en_US.UTF-8".to_string());
+        if !locale
+            .lines()
+            .any(|line| line.trim() == format!("LANG={}", self.target.locale))
+        {
+            issues.push(format!("Locale is not set to {}", self.target.locale));
         }
         let vconsole = fs::read_to_string("/etc/vconsole.conf").unwrap_or_default();
         if !vconsole.contains("KEYMAP=") {
@@ -1235,32 +1866,49 @@ impl RepairContext {
         if !Path::new("/boot/loader/entries/slate.conf").exists() {
             issues.push("Missing /boot/loader/entries/slate.conf".to_string());
         }
+        if let Err(err) = check_mkinitcpio_conf(Path::new(MKINITCPIO_CONF_PATH)) {
+            issues.push(format!("{:#}", err));
+        }
+        Ok(issues)
+    }
+
+    fn inspect_permissions(&self) -> Result<Vec<String>> {
+        let uid = self.target_user()?.uid.as_raw();
+        let paths = foreign_owned_paths(&self.managed_paths()?, &self.target.home, uid)?;
+        let mut issues: Vec<String> = paths
+            .iter()
+            .take(MAX_LISTED_PATHS)
+            .map(|path| format!("Not owned by {}: {}", self.target.username, path.display()))
+            .collect();
+        if paths.len() > MAX_LISTED_PATHS {
+            issues.push(format!("...and {} more", paths.len() - MAX_LISTED_PATHS));
+        }
         Ok(issues)
     }
 
     fn apply_packages(&mut self) -> Result<()> {
         self.ensure_pacman_keyring()?;
-        self.ensure_shell_source()?;
+        ensure_shell_source(&self.shell_source)?;
         let requirements =
             parse_requirements_file(&Path::new(SHELL_REPO_DIR).join("requirements.txt"))?;
-        let packages = merged_package_plan(&requirements);
+        let packages = merged_package_plan(&PackageManifest::load()?, &requirements);
         fetch_ax_binary()?;
         install_packages_with_ax(&self.target.username, &self.target.home, &packages)?;
         Ok(())
     }
 
     fn apply_shell(&mut self) -> Result<()> {
-        self.ensure_shell_source()?;
+        ensure_shell_source(&self.shell_source)?;
         let config_src = Path::new(SHELL_REPO_DIR).join(".config");
         let local_src = Path::new(SHELL_REPO_DIR).join(".local");
         let config_dst = self.target.home.join(".config");
         let local_dst = self.target.home.join(".local");
 
         fs::create_dir_all(&config_dst)?;
-        copy_dir_contents(&config_src, &config_dst)?;
+        copy_dir_contents(&config_src, &config_dst, &self.target.home)?;
         if local_src.exists() {
             fs::create_dir_all(&local_dst)?;
-            copy_dir_contents(&local_src, &local_dst)?;
+            copy_dir_contents(&local_src, &local_dst, &self.target.home)?;
         }
         apply_shell_overrides(&self.target.install_plan(), &self.target.home)?;
         run_simple(
@@ -1271,38 +1919,15 @@ impl RepairContext {
                 self.target.home.to_string_lossy().as_ref(),
             ],
         )?;
-        Ok(())
-    }
-
-    fn apply_user(&mut self) -> Result<()> {
-        if !user_in_group(&self.target.username, "wheel")? {
-            run_simple("usermod", &["-aG", "wheel", &self.target.username])?;
-        }
-
-        let sudoers = "/etc/sudoers";
-        let content = fs::read_to_string(sudoers).context("Failed to read sudoers")?;
-        let mut updated = if content.contains("%wheel ALL=(ALL:ALL) ALL") {
-            content
-        } else {
-            content.replace("# %wheel ALL=(ALL:ALL) ALL", "%wheel ALL=(ALL:ALL) ALL")
-        };
-        if !updated.contains("@includedir /etc/sudoers.d")
-            && !updated.contains("#includedir /etc/sudoers.d")
-        {
-            updated.push_str("\n@includedir /etc/sudoers.d\n");
-        } else {
-            updated = updated.replace("#includedir /etc/sudoers.d", "@includedir /etc/sudoers.d");
-        }
-        fs::write(sudoers, updated)?;
-
-        fs::create_dir_all("/etc/sudoers.d")?;
-        let sudoers_file = format!("/etc/sudoers.d/10-{}", self.target.username);
-        fs::write(
-            &sudoers_file,
-            format!("{} ALL=(ALL:ALL) ALL\n", self.target.username),
-        )?;
-        fs::set_permissions(&sudoers_file, fs::Permissions::from_mode(0o440))?;
+        Ok(())
+    }
+
+    fn apply_user(&mut self) -> Result<()> {
+        if !user_in_group(&self.target.username, "wheel")? {
+            run_simple("usermod", &["-aG", "wheel", &self.target.username])?;
+        }
 
+        configure_sudoers(&self.target.username)?;
         write_user_shell_files(&self.target.home)?;
         run_simple(
             "chown",
@@ -1317,11 +1942,12 @@ impl RepairContext {
 
     fn apply_system(&mut self) -> Result<()> {
         self.ensure_pacman_keyring()?;
-        write_locale_static()?;
+        write_locale_static(&self.target.locale)?;
         write_timezone_static(&self.target.timezone)?;
-        fs::write(
+        privileged::write(
             "/etc/vconsole.conf",
             format!("KEYMAP={}\n", self.target.keymap),
+            WriteOptions::default(),
         )?;
         run_simple("systemctl", &["enable", "NetworkManager"])?;
         run_simple("systemctl", &["enable", "systemd-timesyncd"])?;
@@ -1330,15 +1956,33 @@ impl RepairContext {
     }
 
     fn apply_boot(&mut self) -> Result<()> {
-        write_bootloader_files()?;
+        write_bootloader_files(
+            &self.target.boot_title,
+            self.target.boot_timeout,
+            self.target.preserve_esp,
+        )?;
         Ok(())
     }
 
-    fn ensure_shell_source(&self) -> Result<()> {
-        if Path::new(SHELL_REPO_DIR).exists() {
-            return Ok(());
+    fn apply_permissions(&mut self) -> Result<()> {
+        let user = self.target_user()?;
+        let (uid, gid) = (user.uid.as_raw(), user.gid.as_raw());
+        for path in foreign_owned_paths(&self.managed_paths()?, &self.target.home, uid)? {
+            std::os::unix::fs::lchown(&path, Some(uid), Some(gid))
+                .with_context(|| format!("Failed to chown {}", path.display()))?;
         }
-        fetch_repo_archive(SHELL_ARCHIVE_URL, Path::new(SHELL_REPO_DIR))
+        Ok(())
+    }
+
+    fn target_user(&self) -> Result<nix::unistd::User> {
+        nix::unistd::User::from_name(&self.target.username)?
+            .ok_or_else(|| anyhow!("User not found: {}", self.target.username))
+    }
+
+    /// Paths in the user's home that Slate deploys and the user must own.
+    fn managed_paths(&self) -> Result<Vec<PathBuf>> {
+        ensure_shell_source(&self.shell_source)?;
+        managed_home_paths(Path::new(SHELL_REPO_DIR), &self.target.home)
     }
 
     fn ensure_pacman_keyring(&self) -> Result<()> {
@@ -1365,8 +2009,18 @@ fn run_simple(cmd: &str, args: &[&str]) -> Result<()> {
     bail!("Command failed: {}: {}", cmd, stderr)
 }
 
-fn fetch_repo_archive(url: &str, target_dir: &Path) -> Result<()> {
-    let archive_path = Path::new("/tmp/slate-shell.tar.gz");
+// Never reuses a checkout left behind by an earlier run.
+fn ensure_shell_source(checkout: &OnceCell<TempPath>) -> Result<()> {
+    if checkout.get().is_none() {
+        let fetched = fetch_repo_archive(SHELL_ARCHIVE_URL, Path::new(SHELL_REPO_DIR))?;
+        let _ = checkout.set(fetched);
+    }
+    Ok(())
+}
+
+fn fetch_repo_archive(url: &str, target_dir: &Path) -> Result<TempPath> {
+    let archive = TempPath::new(SHELL_ARCHIVE_PATH);
+    let archive_path = archive.path();
     if archive_path.exists() {
         fs::remove_file(archive_path).context("Failed to remove stale shell archive")?;
     }
@@ -1378,6 +2032,17 @@ fn fetch_repo_archive(url: &str, target_dir: &Path) -> Result<()> {
             )
         })?;
     }
+    fs::create_dir_all(SHELL_STAGING_DIR)
+        .with_context(|| format!("Failed to create {}", SHELL_STAGING_DIR))?;
+    let available = system::available_space(Path::new(SHELL_STAGING_DIR))?;
+    if available < MIN_STAGING_BYTES {
+        bail!(
+            "Only {} MiB free in {}; at least {} MiB is needed to stage the shell repo",
+            available / 1024 / 1024,
+            SHELL_STAGING_DIR,
+            MIN_STAGING_BYTES / 1024 / 1024
+        );
+    }
 
     run_simple(
         "curl",
@@ -1391,6 +2056,7 @@ fn fetch_repo_archive(url: &str, target_dir: &Path) -> Result<()> {
     )?;
     fs::create_dir_all(target_dir)
         .with_context(|| format!("Failed to create {}", target_dir.display()))?;
+    let checkout = TempPath::new(target_dir);
     run_simple(
         "tar",
         &[
@@ -1401,7 +2067,7 @@ fn fetch_repo_archive(url: &str, target_dir: &Path) -> Result<()> {
             target_dir.to_string_lossy().as_ref(),
         ],
     )?;
-    Ok(())
+    Ok(checkout)
 }
 
 fn run_with_input(cmd: &str, args: &[&str], input: &str) -> Result<()> {
@@ -1439,27 +2105,36 @@ fn user_exists(username: &str) -> Result<bool> {
     Ok(status.success())
 }
 
-fn copy_dir_contents(src: &Path, dst: &Path) -> Result<()> {
+/// Refuses any write that would land outside of `root`.
+fn copy_dir_contents(src: &Path, dst: &Path, root: &Path) -> Result<()> {
     if !src.exists() {
         bail!("Required path missing: {}", src.display());
     }
+    let root =
+        fs::canonicalize(root).with_context(|| format!("Failed to resolve {}", root.display()))?;
     for entry in fs::read_dir(src)? {
         let entry = entry?;
-        copy_path(&entry.path(), &dst.join(entry.file_name()))?;
+        copy_path(&entry.path(), &dst.join(entry.file_name()), &root)?;
     }
     Ok(())
 }
 
-fn copy_path(src: &Path, dst: &Path) -> Result<()> {
+fn copy_path(src: &Path, dst: &Path, root: &Path) -> Result<()> {
     let metadata = fs::symlink_metadata(src)?;
     if metadata.is_dir() {
+        ensure_within_root(dst, root)?;
         fs::create_dir_all(dst)?;
+        ensure_within_root(&fs::canonicalize(dst)?, root)?;
         for entry in fs::read_dir(src)? {
             let entry = entry?;
-            copy_path(&entry.path(), &dst.join(entry.file_name()))?;
+            copy_path(&entry.path(), &dst.join(entry.file_name()), root)?;
         }
         return Ok(());
     }
+    if let Some(parent) = dst.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    ensure_within_root(dst, root)?;
     if metadata.file_type().is_symlink() {
         if dst.exists() {
             let _ = fs::remove_file(dst);
@@ -1468,71 +2143,88 @@ fn copy_path(src: &Path, dst: &Path) -> Result<()> {
         std::os::unix::fs::symlink(target, dst)?;
         return Ok(());
     }
-    if let Some(parent) = dst.parent() {
-        fs::create_dir_all(parent)?;
+    // Never write a file through an existing symlink; replace the link instead.
+    if fs::symlink_metadata(dst).is_ok_and(|meta| meta.file_type().is_symlink()) {
+        fs::remove_file(dst)?;
     }
     fs::copy(src, dst)?;
     Ok(())
 }
 
-fn slate_shell_packages() -> Vec<&'static str> {
-    vec![
-        "base-devel",
-        "git",
-        "hyprland",
-        "quickshell",
-        "hyprlock",
-        "hypridle",
-        "xdg-desktop-portal-hyprland",
-        "qt6-wayland",
-        "pipewire",
-        "wireplumber",
-        "pipewire-pulse",
-        "pipewire-alsa",
-        "firefox",
-        "starship",
-        "eza",
-        "bat",
-        "zoxide",
-        "fzf",
-        "ripgrep",
-        "networkmanager",
-        "network-manager-applet",
-        "blueman",
-        "easyeffects",
-        "grim",
-        "slurp",
-        "imagemagick",
-        "sqlite",
-        "upower",
-        "wl-clipboard",
-        "wlsunset",
-        "wtype",
-        "zbar",
-        "glib2",
-        "power-profiles-daemon",
-        "ttf-roboto",
-        "ttf-dejavu",
-        "ttf-liberation",
-        "noto-fonts",
-        "noto-fonts-cjk",
-        "noto-fonts-emoji",
-        "ttf-nerd-fonts-symbols",
-        "gpu-screen-recorder",
-        "adw-gtk-theme",
-        "cpio",
-        "cmake"
-    ]
+/// Catches symlinks such as `.config/x -> ../../etc` shipped in the shell archive.
+fn ensure_within_root(path: &Path, root: &Path) -> Result<()> {
+    let parent = path
+        .parent()
+        .ok_or_else(|| anyhow!("Invalid copy destination {}", path.display()))?;
+    let resolved = fs::canonicalize(parent)
+        .with_context(|| format!("Failed to resolve {}", parent.display()))?;
+    if !resolved.starts_with(root) {
+        bail!(
+            "Refusing to write {} outside of {}",
+            path.display(),
+            root.display()
+        );
+    }
+    Ok(())
 }
 
-fn merged_package_plan(shell_requirements: &[String]) -> Vec<String> {
+/// Read from [`PACKAGE_MANIFEST_PATH`] when present, else the built-in `packages.toml`.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct PackageManifest {
+    /// Pacstrapped into the new root.
+    base: Vec<String>,
+    /// Installed with ax in the chroot stage and checked by repair.
+    desktop: Vec<String>,
+    /// Extra AUR packages, installed with ax after `desktop`.
+    #[serde(default)]
+    aur: Vec<String>,
+}
+
+impl PackageManifest {
+    fn load() -> Result<Self> {
+        Self::load_from(Path::new(PACKAGE_MANIFEST_PATH))
+    }
+
+    fn load_from(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Self::parse(DEFAULT_PACKAGE_MANIFEST)
+                .context("Invalid built-in package manifest");
+        }
+        let raw = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        Self::parse(&raw).with_context(|| format!("Failed to parse {}", path.display()))
+    }
+
+    fn parse(raw: &str) -> Result<Self> {
+        let manifest: Self = toml::from_str(raw)?;
+        if manifest.base.is_empty() {
+            bail!("The base package group cannot be empty");
+        }
+        for pkg in manifest
+            .base
+            .iter()
+            .chain(&manifest.desktop)
+            .chain(&manifest.aur)
+        {
+            if pkg.is_empty() || pkg.starts_with('-') || pkg.chars().any(char::is_whitespace) {
+                bail!("Invalid package name '{}'", pkg);
+            }
+        }
+        Ok(manifest)
+    }
+}
+
+fn merged_package_plan(manifest: &PackageManifest, shell_requirements: &[String]) -> Vec<String> {
     let mut seen = HashSet::new();
     let mut packages = Vec::new();
 
-    for pkg in slate_shell_packages()
-        .into_iter()
-        .map(|pkg| pkg.to_string())
-        .chain(shell_requirements.iter().cloned())
+    for pkg in manifest
+        .desktop
+        .iter()
+        .chain(&manifest.aur)
+        .chain(shell_requirements)
+        .cloned()
     {
         if seen.insert(pkg.clone()) {
             packages.push(pkg);
@@ -1740,6 +2432,52 @@ fn user_in_group(username: &str, group: &str) -> Result<bool> {
     Ok(groups.split_whitespace().any(|item| item == group))
 }
 
+/// The shell archive's `.config` and `.local` entries plus the dotfiles Slate writes.
+fn managed_home_paths(shell_dir: &Path, home: &Path) -> Result<Vec<PathBuf>> {
+    let mut paths = Vec::new();
+    for dir in [".config", ".local"] {
+        let src = shell_dir.join(dir);
+        if src.is_dir() {
+            paths.push(home.join(dir));
+            collect_relative_paths(&src, &home.join(dir), &mut paths)?;
+        }
+    }
+    for name in [".zprofile", ".zshrc", ".gitconfig"] {
+        paths.push(home.join(name));
+    }
+    Ok(paths)
+}
+
+fn collect_relative_paths(src: &Path, dst: &Path, paths: &mut Vec<PathBuf>) -> Result<()> {
+    for entry in
+        fs::read_dir(src).with_context(|| format!("Failed to read directory {}", src.display()))?
+    {
+        let entry = entry?;
+        let target = dst.join(entry.file_name());
+        paths.push(target.clone());
+        if entry.file_type()?.is_dir() {
+            collect_relative_paths(&entry.path(), &target, paths)?;
+        }
+    }
+    Ok(())
+}
+
+/// Skips missing paths and anything on another filesystem than `home`.
+fn foreign_owned_paths(paths: &[PathBuf], home: &Path, uid: u32) -> Result<Vec<PathBuf>> {
+    let home_dev = fs::metadata(home)
+        .with_context(|| format!("Failed to read {}", home.display()))?
+        .dev();
+    let mut found = Vec::new();
+    for path in paths {
+        if let Ok(metadata) = fs::symlink_metadata(path) {
+            if metadata.dev() == home_dev && metadata.uid() != uid {
+                found.push(path.clone());
+            }
+        }
+    }
+    Ok(found)
+}
+
 fn owned_by_user(path: &Path, username: &str) -> Result<bool> {
     let user = nix::unistd::User::from_name(username)?
         .ok_or_else(|| anyhow!("User not found: {}", username))?;
@@ -1747,7 +2485,7 @@ fn owned_by_user(path: &Path, username: &str) -> Result<bool> {
     Ok(metadata.uid() == user.uid.as_raw())
 }
 
-fn service_enabled(service: &str) -> bool {
+pub fn service_enabled(service: &str) -> bool {
     Command::new("systemctl")
         .args(["is-enabled", service])
         .stdout(Stdio::null())
@@ -1773,15 +2511,14 @@ fn install_packages_with_ax(username: &str, user_home: &Path, packages: &[String
 
     fetch_ax_binary()?;
     let sudoers_rule = format!("{} ALL=(ALL) NOPASSWD: ALL\n", username);
-    fs::write(TEMP_AX_SUDOERS_FILE, sudoers_rule)?;
-    fs::set_permissions(TEMP_AX_SUDOERS_FILE, fs::Permissions::from_mode(0o440))?;
+    let sudoers_file = TempPath::new(TEMP_AX_SUDOERS_FILE);
+    privileged::write(sudoers_file.path(), sudoers_rule, WriteOptions::mode(0o440))?;
 
     let mut args = vec!["-S", "--needed", "--noconfirm"];
     args.extend(packages.iter().map(String::as_str));
 
     let result = run_command_as_user(username, user_home, "ax", &args);
-    let cleanup = fs::remove_file(TEMP_AX_SUDOERS_FILE);
-    if let Err(err) = cleanup {
+    if let Err(err) = sudoers_file.close() {
         bail!("Failed to remove temporary ax sudoers file: {}", err);
     }
     result
@@ -1811,6 +2548,82 @@ fn run_command_as_user(username: &str, user_home: &Path, cmd: &str, args: &[&str
     )
 }
 
+/// Human-readable record of the install choices. Never includes the password.
+fn render_install_summary(plan: &InstallPlan) -> String {
+    let layout = plan
+        .subvolumes
+        .iter()
+        .map(|subvol| format!("{} ({})", subvol.name, subvol.mountpoint))
+        .collect::<Vec<_>>()
+        .join(", ");
+    let mut summary = String::from("Slate install summary\n\n");
+    for (label, value) in [
+        ("Hostname", plan.hostname.as_str()),
+        ("Username", plan.username.as_str()),
+        ("Keymap", plan.keymap.as_str()),
+        ("Locale", plan.locale.as_str()),
+        ("Timezone", plan.timezone.as_str()),
+        ("Disk", plan.disk.as_str()),
+        ("Subvolumes", layout.as_str()),
+        ("Mount options", plan.btrfs_options.as_str()),
+        ("Autologin", if plan.autologin { "tty1" } else { "off" }),
+    ] {
+        summary.push_str(&format!("{:<14} {}\n", format!("{}:", label), value));
+    }
+    summary.push_str(
+        "\nNext steps:\n  slate check          verify the system\n  sudo slate repair    reinstall packages, shell files or boot entries\n",
+    );
+    summary
+}
+
+fn render_autologin_override(username: &str) -> String {
+    format!(
+        "{}\n[Service]\nExecStart=\nExecStart=-/usr/bin/agetty --autologin {} --noclear %I $TERM\n",
+        AUTOLOGIN_MARKER, username
+    )
+}
+
+/// Remove the tty1 autologin drop-in, but only if Slate wrote it.
+fn remove_autologin_override() -> Result<()> {
+    let path = Path::new(AUTOLOGIN_OVERRIDE_PATH);
+    if !path.exists() {
+        return Ok(());
+    }
+    let content =
+        fs::read_to_string(path).with_context(|| format!("Failed to read {}", path.display()))?;
+    if !content.starts_with(AUTOLOGIN_MARKER) {
+        bail!(
+            "{} was not created by Slate; remove it manually to disable autologin",
+            path.display()
+        );
+    }
+    fs::remove_file(path).with_context(|| format!("Failed to remove {}", path.display()))
+}
+
+fn configure_sudoers(username: &str) -> Result<()> {
+    let sudoers = "/etc/sudoers";
+    let content = fs::read_to_string(sudoers).context("Failed to read sudoers")?;
+    let mut updated = if content.contains("%wheel ALL=(ALL:ALL) ALL") {
+        content
+    } else {
+        content.replace("# %wheel ALL=(ALL:ALL) ALL", "%wheel ALL=(ALL:ALL) ALL")
+    };
+    if !updated.contains("@includedir /etc/sudoers.d")
+        && !updated.contains("#includedir /etc/sudoers.d")
+    {
+        updated.push_str("\n@includedir /etc/sudoers.d\n");
+    } else {
+        updated = updated.replace("#includedir /etc/sudoers.d", "@includedir /etc/sudoers.d");
+    }
+    privileged::write(sudoers, updated, WriteOptions::backup())?;
+    privileged::write(
+        format!("/etc/sudoers.d/10-{}", username),
+        format!("{} ALL=(ALL:ALL) ALL\n", username),
+        WriteOptions::mode(0o440),
+    )?;
+    Ok(())
+}
+
 fn write_user_shell_files(home: &Path) -> Result<()> {
     fs::write(
         home.join(".zprofile"),
@@ -1823,16 +2636,56 @@ fn write_user_shell_files(home: &Path) -> Result<()> {
     Ok(())
 }
 
-fn write_locale_static() -> Result<()> {
+fn write_locale_static(locale: &str) -> Result<()> {
     let locale_gen = "/etc/locale.gen";
     let content = fs::read_to_string(locale_gen).context("Failed to read locale.gen")?;
-    let updated = content.replace("#en_US.UTF-8 UTF-8", "en_US.UTF-8 UTF-8");
-    fs::write(locale_gen, updated)?;
-    fs::write("/etc/locale.conf", "LANG=en_US.UTF-8\n")?;
+    let updated = enable_locale(&content, locale)?;
+    privileged::write(locale_gen, updated, WriteOptions::default())?;
+    privileged::write(
+        "/etc/locale.conf",
+        format!("LANG={}\n", locale),
+        WriteOptions::default(),
+    )?;
     run_simple("locale-gen", &[])?;
     Ok(())
 }
 
+/// Uncomment `locale` and the en_US.UTF-8 fallback in a locale.gen file.
+fn enable_locale(content: &str, locale: &str) -> Result<String> {
+    let mut found = false;
+    let mut updated = String::new();
+    for line in content.lines() {
+        let entry = line.strip_prefix('#').unwrap_or(line);
+        let name = if entry.starts_with(char::is_whitespace) {
+            None
+        } else {
+            entry.split_whitespace().next()
+        };
+        match name {
+            Some(name) if name == locale || name == DEFAULT_LOCALE => {
+                found |= name == locale;
+                updated.push_str(entry);
+            }
+            _ => updated.push_str(line),
+        }
+        updated.push('\n');
+    }
+    if !found {
+        bail!("Locale {} is not listed in /etc/locale.gen", locale);
+    }
+    Ok(updated)
+}
+
+fn detect_locale() -> Option<String> {
+    let raw = fs::read_to_string("/etc/locale.conf").ok()?;
+    raw.lines().find_map(|line| {
+        line.strip_prefix("LANG=")
+            .map(str::trim)
+            .filter(|value| !value.is_empty())
+            .map(ToOwned::to_owned)
+    })
+}
+
 fn write_timezone_static(timezone: &str) -> Result<()> {
     let target = format!("/usr/share/zoneinfo/{}", timezone);
     if !Path::new(&target).exists() {
@@ -1846,32 +2699,401 @@ fn write_timezone_static(timezone: &str) -> Result<()> {
     Ok(())
 }
 
-fn write_bootloader_files() -> Result<()> {
-    run_simple("bootctl", &["install"])?;
+fn write_bootloader_files(title: &str, timeout: u32, preserve_esp: bool) -> Result<()> {
     let root_device = system::find_mount_source("/")?
         .ok_or_else(|| anyhow!("Failed to determine root mount source"))?;
     let root_uuid = system::get_uuid(&root_device)?;
-    fs::create_dir_all("/boot/loader/entries")?;
-    fs::write(
-        "/boot/loader/loader.conf",
-        "default slate.conf\ntimeout 3\nconsole-mode max\n",
-    )?;
-    fs::write(
-        "/boot/loader/entries/slate.conf",
-        format!(
-            "title Slate\nlinux /vmlinuz-linux\ninitrd /intel-ucode.img\ninitrd /amd-ucode.img\ninitrd /initramfs-linux.img\noptions root=UUID={} rw rootflags=subvol=@\n",
-            root_uuid
-        ),
-    )?;
+    let root_subvol = system::find_mount_subvolume("/")?.unwrap_or_else(|| "@".to_string());
+    install_bootloader(&root_uuid, &root_subvol, title, timeout, preserve_esp)
+}
+
+/// Restores the previous loader files if any step fails.
+fn install_bootloader(
+    root_uuid: &str,
+    root_subvol: &str,
+    title: &str,
+    timeout: u32,
+    preserve_esp: bool,
+) -> Result<()> {
+    let snapshot = FileSnapshot::capture(&[LOADER_CONF_PATH, BOOT_ENTRY_PATH])?;
+    // A shared ESP keeps its owner's loader.conf; Slate only adds its entry.
+    let keep_loader_conf = preserve_esp && Path::new(LOADER_CONF_PATH).exists();
+    let mut step = "bootctl install";
+    let result = (|| -> Result<()> {
+        run_simple("bootctl", &["install"])?;
+
+        step = "mkinitcpio hooks";
+        check_mkinitcpio_conf(Path::new(MKINITCPIO_CONF_PATH))?;
+        if !Path::new("/boot/initramfs-linux.img").exists() {
+            step = "mkinitcpio";
+            run_simple("mkinitcpio", &["-P"])?;
+        }
+
+        step = "loader entry write";
+        if !keep_loader_conf {
+            privileged::write(
+                LOADER_CONF_PATH,
+                render_loader_conf(timeout),
+                WriteOptions::default(),
+            )?;
+        }
+        privileged::write(
+            BOOT_ENTRY_PATH,
+            render_boot_entry(title, root_uuid, root_subvol),
+            WriteOptions::default(),
+        )?;
+        Ok(())
+    })();
+
+    if let Err(err) = result {
+        let rollback = match snapshot.restore() {
+            Ok(()) => "Previous loader files were restored.".to_string(),
+            Err(restore_err) => {
+                format!("Restoring previous loader files failed: {:#}.", restore_err)
+            }
+        };
+        bail!(
+            "Boot configuration failed at {}: {:#}\n{}\nThe system may not boot until this is fixed. Run `bootctl install`, `mkinitcpio -P` and `slate repair` (boot group) on the target system, via `arch-chroot /mnt` from the live ISO if needed.",
+            step,
+            err,
+            rollback
+        );
+    }
+    Ok(())
+}
+
+fn render_loader_conf(timeout: u32) -> String {
+    format!(
+        "default slate.conf\ntimeout {}\nconsole-mode max\n",
+        timeout
+    )
+}
+
+fn render_boot_entry(title: &str, root_uuid: &str, root_subvol: &str) -> String {
+    format!(
+        "title {}\nlinux /vmlinuz-linux\ninitrd /intel-ucode.img\ninitrd /amd-ucode.img\ninitrd /initramfs-linux.img\noptions root=UUID={} rw rootflags=subvol={}\n",
+        title, root_uuid, root_subvol
+    )
+}
+
+fn check_mkinitcpio_conf(path: &Path) -> Result<()> {
+    let conf =
+        fs::read_to_string(path).with_context(|| format!("Failed to read {}", path.display()))?;
+    check_mkinitcpio_hooks(&conf)
+}
+
+fn check_mkinitcpio_hooks(conf: &str) -> Result<()> {
+    let line = conf
+        .lines()
+        .map(str::trim)
+        .rfind(|line| line.starts_with("HOOKS="))
+        .ok_or_else(|| anyhow!("No HOOKS line found in mkinitcpio.conf"))?;
+    let hooks: Vec<&str> = line
+        .trim_start_matches("HOOKS=")
+        .trim_matches(|ch| ch == '(' || ch == ')' || ch == '"')
+        .split_whitespace()
+        .collect();
+    let position = |hook: &str| hooks.iter().position(|item| *item == hook);
+
+    if position("base").is_none() && position("systemd").is_none() {
+        bail!("mkinitcpio HOOKS is missing 'base' or 'systemd': {}", line);
+    }
+    for required in ["block", "filesystems"] {
+        if position(required).is_none() {
+            bail!("mkinitcpio HOOKS is missing '{}': {}", required, line);
+        }
+    }
+    if position("block") > position("filesystems") {
+        bail!(
+            "mkinitcpio HOOKS must list 'block' before 'filesystems': {}",
+            line
+        );
+    }
     Ok(())
 }
 
+/// In-memory copy of a set of files, used to roll back a multi-file write.
+struct FileSnapshot {
+    files: Vec<(PathBuf, Option<Vec<u8>>)>,
+}
+
+impl FileSnapshot {
+    fn capture<P: AsRef<Path>>(paths: &[P]) -> Result<Self> {
+        let mut files = Vec::new();
+        for path in paths {
+            let path = path.as_ref();
+            let content = if path.exists() {
+                Some(fs::read(path).with_context(|| format!("Failed to read {}", path.display()))?)
+            } else {
+                None
+            };
+            files.push((path.to_path_buf(), content));
+        }
+        Ok(Self { files })
+    }
+
+    fn restore(&self) -> Result<()> {
+        for (path, content) in &self.files {
+            match content {
+                Some(bytes) => privileged::write(path, bytes, WriteOptions::default())
+                    .with_context(|| format!("Failed to restore {}", path.display()))?,
+                None if path.exists() => fs::remove_file(path)
+                    .with_context(|| format!("Failed to remove {}", path.display()))?,
+                None => {}
+            }
+        }
+        Ok(())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::{
-        detect_timezone, normalize_package_name, parse_requirements, sanitize_for_log,
-        set_hypr_keymap, Checkpoint, InstallPlan, StageId,
+        check_esp_contents, check_esp_free_space, check_mkinitcpio_hooks, check_preserved_esp,
+        copy_dir_contents, default_boot_timeout, default_boot_title, default_btrfs_options,
+        default_locale, default_subvolumes, detect_timezone, enable_locale, find_existing_install,
+        foreign_owned_paths, fstab_mounts, managed_home_paths, merged_package_plan,
+        normalize_package_name, parse_requirements, parse_subvolumes, persist_migrated_plan,
+        read_plan_from, render_autologin_override, render_boot_entry, render_install_summary,
+        render_loader_conf, sanitize_for_log, set_hypr_keymap, validate_btrfs_options,
+        validate_hostname, validate_username, Checkpoint, FileSnapshot, InstallPlan,
+        PackageManifest, StageId, DEFAULT_PACKAGE_MANIFEST, PLAN_VERSION,
     };
+    use std::fs;
+
+    #[test]
+    fn autologin_override_is_marked_as_slate_owned() {
+        let rendered = render_autologin_override("alice");
+        assert!(rendered.starts_with(super::AUTOLOGIN_MARKER));
+        assert!(rendered.contains("--autologin alice --noclear"));
+    }
+
+    #[test]
+    fn builtin_package_manifest_parses_and_rejects_bad_names() {
+        let manifest = PackageManifest::parse(DEFAULT_PACKAGE_MANIFEST).unwrap();
+        assert!(manifest.base.iter().any(|pkg| pkg == "linux"));
+        assert!(manifest.desktop.iter().any(|pkg| pkg == "hyprland"));
+        assert!(manifest.aur.is_empty());
+
+        assert!(PackageManifest::parse("base = []\ndesktop = []").is_err());
+        assert!(
+            PackageManifest::parse("base = [\"base\"]\ndesktop = []\ndesktp = [\"kitty\"]")
+                .is_err()
+        );
+        assert!(PackageManifest::parse("base = [\"--overwrite\"]\ndesktop = []").is_err());
+        let custom = PackageManifest::parse("base = [\"base\"]\ndesktop = [\"kitty\"]").unwrap();
+        assert_eq!(
+            merged_package_plan(&custom, &["kitty".to_string(), "swww".to_string()]),
+            vec!["kitty".to_string(), "swww".to_string()]
+        );
+    }
+
+    #[test]
+    fn enable_locale_uncomments_entries_but_not_header_examples() {
+        let stock = "# Examples:\n# en_US.UTF-8 UTF-8\n# de_DE ISO-8859-1\n\n#de_DE.UTF-8 UTF-8\n#de_DE ISO-8859-1\n#en_US.UTF-8 UTF-8\n#fr_FR.UTF-8 UTF-8\n";
+        let updated = enable_locale(stock, "de_DE.UTF-8").unwrap();
+        assert_eq!(
+            updated,
+            "# Examples:\n# en_US.UTF-8 UTF-8\n# de_DE ISO-8859-1\n\nde_DE.UTF-8 UTF-8\n#de_DE ISO-8859-1\nen_US.UTF-8 UTF-8\n#fr_FR.UTF-8 UTF-8\n"
+        );
+        assert!(enable_locale(stock, "xx_XX.UTF-8").is_err());
+    }
+
+    #[test]
+    fn plan_without_autologin_field_defaults_to_enabled() {
+        let plan: InstallPlan = serde_json::from_str(
+            r#"{"disk":"/dev/vda","hostname":"h","username":"u","password":"p","keymap":"us","timezone":"UTC","git_name":"","git_email":"","desktop_profile":"Slate"}"#,
+        )
+        .unwrap();
+        assert!(plan.autologin);
+    }
+
+    #[test]
+    fn read_plan_from_migrates_in_memory_and_persist_rewrites_without_backup() {
+        let dir = std::env::temp_dir().join(format!("slate-plan-migrate-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("install-plan.json");
+        fs::write(
+            &path,
+            r#"{"disk":"/dev/vda","hostname":"h","username":"u","password":"p","keymap":"us","timezone":"UTC","git_name":"","git_email":"","desktop_profile":"Slate"}"#,
+        )
+        .unwrap();
+
+        let plan = read_plan_from(&path).unwrap();
+        let untouched = !fs::read_to_string(&path).unwrap().contains("version");
+        persist_migrated_plan(&path).unwrap();
+        let saved: InstallPlan = serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+        let has_backup = dir.join("install-plan.json.slate.bak").exists();
+        let _ = fs::remove_dir_all(&dir);
+
+        assert_eq!(plan.version, PLAN_VERSION);
+        assert!(untouched);
+        assert_eq!(saved.version, PLAN_VERSION);
+        assert_eq!(saved.subvolumes, default_subvolumes());
+        assert!(!has_backup);
+    }
+
+    #[test]
+    fn migrate_rejects_plans_from_newer_versions() {
+        let mut plan: InstallPlan = serde_json::from_str(
+            r#"{"disk":"/dev/vda","hostname":"h","username":"u","password":"p","keymap":"us","timezone":"UTC","git_name":"","git_email":"","desktop_profile":"Slate","version":99}"#,
+        )
+        .unwrap();
+        assert!(plan.migrate().is_err());
+    }
+
+    #[test]
+    fn foreign_owned_paths_only_covers_deployed_files() {
+        use std::os::unix::fs::MetadataExt;
+
+        let root = std::env::temp_dir().join(format!("slate-owner-test-{}", std::process::id()));
+        let shell = root.join("shell");
+        let home = root.join("home");
+        fs::create_dir_all(shell.join(".config/hypr")).unwrap();
+        fs::write(shell.join(".config/hypr/hyprland.conf"), "conf").unwrap();
+        fs::create_dir_all(home.join(".config/hypr")).unwrap();
+        fs::write(home.join(".config/hypr/hyprland.conf"), "conf").unwrap();
+        fs::create_dir_all(home.join(".local/share/containers/storage")).unwrap();
+        fs::write(home.join(".zshrc"), "rc").unwrap();
+        let uid = fs::metadata(&home).unwrap().uid();
+
+        let managed = managed_home_paths(&shell, &home).unwrap();
+        let none = foreign_owned_paths(&managed, &home, uid).unwrap();
+        let all = foreign_owned_paths(&managed, &home, uid + 1).unwrap();
+        let _ = fs::remove_dir_all(&root);
+
+        assert!(none.is_empty());
+        assert_eq!(
+            all,
+            vec![
+                home.join(".config"),
+                home.join(".config/hypr"),
+                home.join(".config/hypr/hyprland.conf"),
+                home.join(".zshrc"),
+            ]
+        );
+        assert!(!managed
+            .iter()
+            .any(|path| path.starts_with(home.join(".local/share/containers"))));
+    }
+
+    #[test]
+    fn validates_hostname_and_username_rules() {
+        assert!(validate_hostname("slate-box1").is_ok());
+        assert!(validate_hostname("-slate").is_err());
+        assert!(validate_hostname("slate.local").is_err());
+        assert!(validate_hostname(&"a".repeat(64)).is_err());
+
+        assert!(validate_username("alice").is_ok());
+        assert!(validate_username("_svc-1").is_ok());
+        assert!(validate_username("Alice").is_err());
+        assert!(validate_username("1alice").is_err());
+        assert!(validate_username("root").is_err());
+    }
+
+    #[test]
+    fn preserved_esp_must_be_large_fat32_esp() {
+        let esp = "c12a7328-f81f-11d2-ba4b-00a0c93ec93b";
+        assert!(check_preserved_esp("/dev/vda1", &format!("vfat {} 1073741824\n", esp)).is_ok());
+        assert!(check_preserved_esp("/dev/vda1", &format!("vfat {} 104857600\n", esp)).is_err());
+        assert!(check_preserved_esp("/dev/vda1", &format!("ext4 {} 1073741824\n", esp)).is_err());
+        assert!(check_preserved_esp(
+            "/dev/vda1",
+            "vfat 0fc63daf-8483-4772-8e79-3d69d8477de4 1073741824\n"
+        )
+        .is_err());
+        assert!(check_preserved_esp("/dev/vda1", "").is_err());
+
+        assert!(check_esp_free_space("/dev/vda1", 300 * 1024 * 1024).is_ok());
+        assert!(check_esp_free_space("/dev/vda1", 40 * 1024 * 1024).is_err());
+    }
+
+    #[test]
+    fn preserved_esp_must_not_hold_slate_boot_files() {
+        let esp = std::env::temp_dir().join(format!("slate-esp-test-{}", std::process::id()));
+        fs::create_dir_all(esp.join("loader/entries")).unwrap();
+        fs::write(esp.join("loader/loader.conf"), "timeout 3\n").unwrap();
+        let clean = check_esp_contents("/dev/vda1", &esp).is_ok();
+
+        fs::write(esp.join("initramfs-linux-fallback.img"), "").unwrap();
+        let image = check_esp_contents("/dev/vda1", &esp).is_err();
+        fs::remove_file(esp.join("initramfs-linux-fallback.img")).unwrap();
+
+        fs::write(esp.join("loader/entries/slate.conf"), "title Slate\n").unwrap();
+        let entry = check_esp_contents("/dev/vda1", &esp).is_err();
+        let _ = fs::remove_dir_all(&esp);
+
+        assert!(clean);
+        assert!(image);
+        assert!(entry);
+    }
+
+    #[test]
+    fn validates_btrfs_mount_options() {
+        assert!(validate_btrfs_options(&default_btrfs_options()).is_ok());
+        assert!(validate_btrfs_options("rw,noatime,compress=zstd:3,discard=async,ssd").is_ok());
+        assert!(validate_btrfs_options("rw,relatime,compress-force=zlib:9,nossd").is_ok());
+        assert!(validate_btrfs_options("rw,compress=zstd:22").is_err());
+        assert!(validate_btrfs_options("rw,compres=zstd").is_err());
+        assert!(validate_btrfs_options("rw,subvol=@").is_err());
+        assert!(validate_btrfs_options("rw,,noatime").is_err());
+    }
+
+    #[test]
+    fn mkinitcpio_hooks_need_block_before_filesystems() {
+        let stock = "# HOOKS=(base)\nHOOKS=(base udev autodetect microcode modconf kms keyboard keymap consolefont block filesystems fsck)\n";
+        assert!(check_mkinitcpio_hooks(stock).is_ok());
+        assert!(check_mkinitcpio_hooks(
+            "HOOKS=(base systemd autodetect keyboard sd-vconsole block filesystems fsck)"
+        )
+        .is_ok());
+        assert!(check_mkinitcpio_hooks("HOOKS=(base udev autodetect block fsck)").is_err());
+        assert!(check_mkinitcpio_hooks("HOOKS=(base udev filesystems block)").is_err());
+        assert!(check_mkinitcpio_hooks("MODULES=()").is_err());
+    }
+
+    #[test]
+    fn install_summary_records_choices_but_not_password() {
+        let plan = InstallPlan {
+            disk: "/dev/vda".into(),
+            hostname: "slatebox".into(),
+            username: "alice".into(),
+            password: "hunter2-secret".into(),
+            keymap: "de".into(),
+            timezone: "Europe/Berlin".into(),
+            git_name: String::new(),
+            git_email: String::new(),
+            desktop_profile: "Slate".into(),
+            locale: default_locale(),
+            subvolumes: default_subvolumes(),
+            autologin: false,
+            preserve_esp: false,
+            btrfs_options: default_btrfs_options(),
+            boot_title: default_boot_title(),
+            boot_timeout: default_boot_timeout(),
+            version: PLAN_VERSION,
+        };
+
+        let summary = render_install_summary(&plan);
+
+        assert!(summary.contains("Hostname:      slatebox"));
+        assert!(summary.contains("Username:      alice"));
+        assert!(summary.contains("Timezone:      Europe/Berlin"));
+        assert!(summary.contains("Autologin:     off"));
+        assert!(!summary.contains("hunter2-secret"));
+    }
+
+    #[test]
+    fn renders_configured_boot_title_and_timeout() {
+        let loader = render_loader_conf(0);
+        let entry = render_boot_entry("Slate (work)", "1234-abcd", "@");
+
+        assert!(loader.contains("timeout 0\n"));
+        assert!(loader.starts_with("default slate.conf\n"));
+        assert!(entry.starts_with("title Slate (work)\n"));
+        assert!(entry.contains("root=UUID=1234-abcd rw rootflags=subvol=@"));
+        assert!(super::validate_boot_title("Slate\ntimeout 0").is_err());
+    }
 
     #[test]
     fn install_plan_validation_rejects_missing_fields() {
@@ -1885,11 +3107,51 @@ mod tests {
             git_name: String::new(),
             git_email: String::new(),
             desktop_profile: "slate".into(),
+            locale: default_locale(),
+            subvolumes: default_subvolumes(),
+            autologin: true,
+            preserve_esp: false,
+            btrfs_options: default_btrfs_options(),
+            boot_title: default_boot_title(),
+            boot_timeout: default_boot_timeout(),
+            version: PLAN_VERSION,
         };
 
         assert!(plan.validate().is_err());
     }
 
+    #[test]
+    fn parses_subvolume_layout() {
+        let layout = parse_subvolumes("@=/, @home=/home,@var=/var").unwrap();
+        assert_eq!(layout.len(), 3);
+        assert_eq!(layout[2].name, "@var");
+        assert_eq!(layout[2].mountpoint, "/var");
+    }
+
+    #[test]
+    fn subvolume_layout_requires_root_and_unique_mountpoints() {
+        assert!(parse_subvolumes("@home=/home").is_err());
+        assert!(parse_subvolumes("@=/,@home=/home,@other=/home").is_err());
+        assert!(parse_subvolumes("@=/,@boot=/boot").is_err());
+        assert!(parse_subvolumes("@=/,@home").is_err());
+        assert!(parse_subvolumes("@=/,..=/home").is_err());
+        assert!(parse_subvolumes(".=/").is_err());
+        assert!(parse_subvolumes("=/").is_err());
+        assert!(parse_subvolumes("@=/,@home=/home/").is_err());
+        assert!(parse_subvolumes("@=/,@home=//home").is_err());
+        assert!(parse_subvolumes("@=/,@log=/var//log").is_err());
+        assert!(parse_subvolumes("@=/,@home=/home,@other=/home/").is_err());
+        assert!(parse_subvolumes("@=/,@log=/var/log").is_ok());
+    }
+
+    #[test]
+    fn fstab_check_matches_mountpoint_column() {
+        let fstab =
+            "# /dev/sda2\nUUID=abc / btrfs subvol=/@ 0 0\nUUID=abc /home btrfs subvol=/@home 0 0\n";
+        assert!(fstab_mounts(fstab, "/home"));
+        assert!(!fstab_mounts(fstab, "/var/log"));
+    }
+
     #[test]
     fn sanitize_for_log_strips_escape_sequences() {
         assert_eq!(sanitize_for_log("\u{1b}[31merror\u{1b}[0m"), "error");
@@ -1949,4 +3211,113 @@ mod tests {
     fn timezone_detection_handles_missing_link() {
         let _ = detect_timezone();
     }
+
+    #[test]
+    fn copy_dir_contents_rejects_symlink_traversal() {
+        let base = std::env::temp_dir().join(format!("slate-copy-test-{}", std::process::id()));
+        let src = base.join("src");
+        let home = base.join("home");
+        let dst = home.join(".config");
+        let outside = base.join("outside");
+        fs::create_dir_all(src.join("hypr")).unwrap();
+        fs::write(src.join("hypr/hyprland.conf"), "planted").unwrap();
+        fs::create_dir_all(&dst).unwrap();
+        fs::create_dir_all(&outside).unwrap();
+        std::os::unix::fs::symlink("../../outside", dst.join("hypr")).unwrap();
+
+        let result = copy_dir_contents(&src, &dst, &home);
+        let escaped = outside.join("hyprland.conf").exists();
+        let _ = fs::remove_dir_all(&base);
+
+        assert!(result.is_err());
+        assert!(!escaped);
+    }
+
+    #[test]
+    fn file_snapshot_restores_and_removes() {
+        let base = std::env::temp_dir().join(format!("slate-snapshot-test-{}", std::process::id()));
+        fs::create_dir_all(&base).unwrap();
+        let existing = base.join("loader.conf");
+        let created = base.join("slate.conf");
+        fs::write(&existing, "timeout 5\n").unwrap();
+
+        let snapshot = FileSnapshot::capture(&[&existing, &created]).unwrap();
+        fs::write(&existing, "timeout 3\n").unwrap();
+        fs::write(&created, "title Slate\n").unwrap();
+        snapshot.restore().unwrap();
+
+        let restored = fs::read_to_string(&existing).unwrap();
+        let leftover = created.exists();
+        let _ = fs::remove_dir_all(&base);
+
+        assert_eq!(restored, "timeout 5\n");
+        assert!(!leftover);
+    }
+
+    #[test]
+    fn resume_requires_existing_layout_and_bootstrap() {
+        let top = std::env::temp_dir().join(format!("slate-resume-test-{}", std::process::id()));
+        let slate_dir = top.join("@/etc/slate");
+        fs::create_dir_all(&slate_dir).unwrap();
+        let plan = InstallPlan {
+            disk: "/dev/sda".into(),
+            hostname: "host".into(),
+            username: "user".into(),
+            password: "pass".into(),
+            keymap: "us".into(),
+            timezone: "UTC".into(),
+            git_name: String::new(),
+            git_email: String::new(),
+            desktop_profile: "slate".into(),
+            locale: default_locale(),
+            subvolumes: default_subvolumes(),
+            autologin: true,
+            preserve_esp: false,
+            btrfs_options: default_btrfs_options(),
+            boot_title: default_boot_title(),
+            boot_timeout: default_boot_timeout(),
+            version: PLAN_VERSION,
+        };
+        fs::write(
+            slate_dir.join("install-plan.json"),
+            serde_json::to_vec(&plan).unwrap(),
+        )
+        .unwrap();
+        let missing_subvols = find_existing_install(&top).is_err();
+
+        let checkpoint = Checkpoint {
+            active_stage: Some(StageId::StageApply),
+            completed_stages: vec![StageId::Collect, StageId::PrepareDisk, StageId::Bootstrap],
+            last_error: Some("network down".into()),
+        };
+        let copy_dir = top.join("@copy/etc/slate");
+        fs::create_dir_all(&copy_dir).unwrap();
+        fs::write(
+            copy_dir.join("install-plan.json"),
+            serde_json::to_vec(&plan).unwrap(),
+        )
+        .unwrap();
+        fs::write(
+            copy_dir.join("checkpoint.json"),
+            serde_json::to_vec(&checkpoint).unwrap(),
+        )
+        .unwrap();
+
+        for subvol in &plan.subvolumes {
+            fs::create_dir_all(top.join(&subvol.name)).unwrap();
+        }
+        let missing_checkpoint = find_existing_install(&top).is_err();
+
+        fs::write(
+            slate_dir.join("checkpoint.json"),
+            serde_json::to_vec(&checkpoint).unwrap(),
+        )
+        .unwrap();
+        let found = find_existing_install(&top);
+        let _ = fs::remove_dir_all(&top);
+
+        assert!(missing_subvols);
+        assert!(missing_checkpoint);
+        assert_eq!(found.unwrap().0.username, "user");
+    }
 }
@@ -1,14 +1,22 @@
 mod commands;
+mod error;
 mod installer;
 mod system;
 mod tui;
 
-use clap::{Parser, Subcommand};
+use clap::{Args, Parser, Subcommand};
+use error::{Classify, SlateError};
+use std::process::ExitCode;
 
 #[derive(Parser)]
 #[command(name = "slate")]
 #[command(about = "Arch Linux installer for the Slate shell", version = "0.2.0")]
+#[command(after_help = error::exit_codes_help())]
 struct Cli {
+    /// Print errors as JSON on stderr, and `check` results as JSON on stdout
+    #[arg(long, global = true)]
+    json: bool,
+
     #[command(subcommand)]
     command: Commands,
 }
@@ -16,15 +24,23 @@ struct Cli {
 #[derive(Subcommand)]
 enum Commands {
     /// Start the interactive TUI installer
-    Install,
+    Install(InstallArgs),
 
     /// Repair an existing Slate system from the command line
-    Repair,
+    Repair {
+        /// Only report what needs repair, changing nothing; exit 8 if anything does
+        #[arg(long)]
+        check: bool,
+    },
 
     /// Verify system requirements before installation
     Check {
         #[arg(long)]
         verbose: bool,
+
+        /// Also diagnose DNS, TCP, HTTPS and pacman mirror reachability separately
+        #[arg(long)]
+        network: bool,
     },
 
     /// Internal stage runner (hidden)
@@ -32,23 +48,120 @@ enum Commands {
     ChrootStage,
 }
 
-fn main() -> anyhow::Result<()> {
+#[derive(Args)]
+struct InstallArgs {
+    /// Btrfs subvolume layout as name=mountpoint pairs, e.g. "@=/,@home=/home"
+    #[arg(long, value_name = "LAYOUT")]
+    subvols: Option<String>,
+
+    /// Pre-fill the hostname field of the installer form
+    #[arg(long)]
+    hostname: Option<String>,
+
+    /// Pre-fill the username field of the installer form
+    #[arg(long)]
+    username: Option<String>,
+
+    /// Keep an existing FAT ESP on partition 1 (dual-boot); other partitions are still deleted
+    #[arg(long)]
+    preserve_esp: bool,
+
+    /// Btrfs mount options for every subvolume, e.g. "rw,noatime,compress=zstd:3,ssd"
+    #[arg(long, value_name = "OPTIONS")]
+    btrfs_opts: Option<String>,
+
+    /// Title of the systemd-boot menu entry
+    #[arg(long, value_name = "TITLE")]
+    boot_title: Option<String>,
+
+    /// Seconds the boot menu waits before booting (0 hides it)
+    #[arg(long, value_name = "SECONDS")]
+    boot_timeout: Option<u32>,
+
+    /// Remount a disk Slate already partitioned and re-run the chroot stage
+    #[arg(
+        long,
+        value_name = "DISK",
+        conflicts_with_all = ["subvols", "hostname", "username", "preserve_esp", "btrfs_opts", "boot_title", "boot_timeout"]
+    )]
+    resume: Option<String>,
+}
+
+fn main() -> ExitCode {
     let cli = Cli::parse();
+    let json = cli.json;
 
+    match run(cli) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            report_error(&err, json);
+            ExitCode::from(error::exit_code(&err))
+        }
+    }
+}
+
+fn run(cli: Cli) -> anyhow::Result<()> {
+    let json = cli.json;
     match cli.command {
-        Commands::Install => {
-            commands::forge()?;
+        Commands::Install(args) => {
+            if let Some(disk) = &args.resume {
+                commands::forge_resume(disk).classify(SlateError::Install)?;
+            } else {
+                let options = install_options(args).classify(SlateError::Validation)?;
+                commands::forge(options).classify(SlateError::Install)?;
+            }
         }
-        Commands::Repair => {
-            commands::repair()?;
+        Commands::Repair { check } => {
+            commands::repair(check).classify(SlateError::Repair)?;
         }
-        Commands::Check { verbose } => {
-            commands::check(verbose)?;
+        Commands::Check { verbose, network } => {
+            commands::check(verbose, network, json).classify(SlateError::Environment)?;
         }
         Commands::ChrootStage => {
-            commands::chroot_stage()?;
+            commands::chroot_stage().classify(SlateError::Install)?;
         }
     }
 
     Ok(())
 }
+
+fn install_options(args: InstallArgs) -> anyhow::Result<installer::InstallOptions> {
+    let mut options = installer::InstallOptions::default();
+    if let Some(spec) = args.subvols {
+        options.subvolumes = installer::parse_subvolumes(&spec)?;
+    }
+    if let Some(hostname) = &args.hostname {
+        installer::validate_hostname(hostname)?;
+    }
+    if let Some(username) = &args.username {
+        installer::validate_username(username)?;
+    }
+    options.hostname = args.hostname;
+    options.username = args.username;
+    options.preserve_esp = args.preserve_esp;
+    if let Some(btrfs_opts) = args.btrfs_opts {
+        installer::validate_btrfs_options(&btrfs_opts)?;
+        options.btrfs_options = btrfs_opts;
+    }
+    if let Some(title) = args.boot_title {
+        installer::validate_boot_title(&title)?;
+        options.boot_title = title;
+    }
+    if let Some(timeout) = args.boot_timeout {
+        options.boot_timeout = timeout;
+    }
+    Ok(options)
+}
+
+fn report_error(err: &anyhow::Error, json: bool) {
+    if json {
+        let payload = serde_json::json!({
+            "error": err.to_string(),
+            "context": err.chain().skip(1).map(ToString::to_string).collect::<Vec<_>>(),
+            "code": error::exit_code(err),
+        });
+        eprintln!("{}", payload);
+    } else {
+        eprintln!("Error: {:?}", err);
+    }
+}
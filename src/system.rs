@@ -1,4 +1,12 @@
-use anyhow::{bail, Context, Result};
+pub mod network;
+pub mod privileged;
+pub mod temp;
+
+pub use privileged::WriteOptions;
+pub use temp::TempPath;
+
+use anyhow::{anyhow, bail, Context, Result};
+use nix::unistd::User;
 use std::fs;
 use std::path::Path;
 
@@ -15,6 +23,49 @@ pub fn find_mount_source(mount_point: &str) -> Result<Option<String>> {
     Ok(None)
 }
 
+/// Return the btrfs subvolume mounted at `mount_point`, without its leading slash.
+pub fn find_mount_subvolume(mount_point: &str) -> Result<Option<String>> {
+    let mounts = fs::read_to_string("/proc/mounts").context("Failed to read /proc/mounts")?;
+
+    for line in mounts.lines() {
+        let parts: Vec<&str> = line.split_whitespace().collect();
+        if parts.len() >= 4 && parts[1] == mount_point {
+            return Ok(parts[3]
+                .split(',')
+                .find_map(|option| option.strip_prefix("subvol="))
+                .map(|subvol| subvol.trim_start_matches('/').to_string())
+                .filter(|subvol| !subvol.is_empty()));
+        }
+    }
+
+    Ok(None)
+}
+
+/// Resolve the human user behind this process, looking through sudo.
+///
+/// Prefers `$SUDO_USER`, then `$USER`, skipping root, and resolves the account
+/// through the passwd database so the home directory is never `/root`.
+pub fn real_user() -> Result<User> {
+    let username = ["SUDO_USER", "USER"]
+        .iter()
+        .filter_map(|var| std::env::var(var).ok())
+        .find(|value| !value.trim().is_empty() && value != "root")
+        .ok_or_else(|| {
+            anyhow!("Unable to resolve current non-root user; run through sudo from your account")
+        })?;
+
+    User::from_name(&username)
+        .context("Failed to query target user")?
+        .ok_or_else(|| anyhow!("Target user does not exist: {}", username))
+}
+
+/// Bytes available to unprivileged writers on the filesystem holding `path`.
+pub fn available_space(path: &Path) -> Result<u64> {
+    let stats = nix::sys::statvfs::statvfs(path)
+        .with_context(|| format!("Failed to stat filesystem of {}", path.display()))?;
+    Ok(stats.blocks_available() as u64 * stats.fragment_size() as u64)
+}
+
 /// Extract filesystem/LUKS UUID by scanning /dev/disk/by-uuid/
 pub fn get_uuid(device_path: &str) -> Result<String> {
     let uuid_dir = Path::new("/dev/disk/by-uuid");
@@ -53,6 +104,30 @@ pub fn partition_path(device: &str, part_num: u32) -> String {
     }
 }
 
+/// Partition numbers currently known to the kernel for `device`, e.g. [1, 2, 3].
+pub fn partition_numbers(device: &str) -> Result<Vec<u32>> {
+    let name = Path::new(device)
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let block_dir = Path::new("/sys/class/block").join(&name);
+    let mut numbers = Vec::new();
+
+    for entry in fs::read_dir(&block_dir)
+        .with_context(|| format!("Could not access {}", block_dir.display()))?
+    {
+        let number_file = entry?.path().join("partition");
+        if let Ok(value) = fs::read_to_string(number_file) {
+            if let Ok(number) = value.trim().parse() {
+                numbers.push(number);
+            }
+        }
+    }
+
+    numbers.sort_unstable();
+    Ok(numbers)
+}
+
 #[derive(Debug, Clone)]
 pub struct BlockDevice {
     pub path: String,
@@ -182,6 +257,29 @@ pub fn list_timezones() -> Result<Vec<String>> {
     Ok(zones)
 }
 
+/// List the locales offered in /etc/locale.gen, commented out or not.
+pub fn list_locales() -> Result<Vec<String>> {
+    let raw = fs::read_to_string("/etc/locale.gen").context("Failed to read /etc/locale.gen")?;
+    let mut locales: Vec<String> = raw
+        .lines()
+        .filter_map(|line| {
+            let entry = line.strip_prefix('#').unwrap_or(line);
+            // "# en_US.UTF-8 UTF-8" in the header is an example, not an entry.
+            if entry.starts_with(char::is_whitespace) {
+                return None;
+            }
+            let mut fields = entry.split_whitespace();
+            match (fields.next(), fields.next(), fields.next()) {
+                (Some(name), Some(_charset), None) => Some(name.to_string()),
+                _ => None,
+            }
+        })
+        .collect();
+    locales.sort();
+    locales.dedup();
+    Ok(locales)
+}
+
 #[cfg(test)]
 mod tests {
     use super::partition_path;
@@ -0,0 +1,164 @@
+use anyhow::{anyhow, bail, Context, Result};
+use std::fs;
+use std::net::{SocketAddr, TcpStream, ToSocketAddrs};
+use std::process::{Command, Stdio};
+use std::sync::mpsc;
+use std::thread;
+use std::time::Duration;
+
+const PROBE_HOST: &str = "archlinux.org";
+const MIRRORLIST_PATH: &str = "/etc/pacman.d/mirrorlist";
+const DNS_TIMEOUT: Duration = Duration::from_secs(5);
+const TCP_TIMEOUT: Duration = Duration::from_secs(5);
+const HTTPS_TIMEOUT_SECS: &str = "10";
+
+/// Outcome of one network probe.
+pub struct Probe {
+    pub name: &'static str,
+    pub result: Result<String>,
+}
+
+/// Probe DNS, raw TCP, HTTPS and the first pacman mirror separately, so a
+/// failure points at the layer that is broken. Each probe has its own timeout.
+pub fn diagnose() -> Vec<Probe> {
+    let dns = resolve(PROBE_HOST);
+    let (tcp, https, mirror) = match &dns {
+        Ok(addrs) => (
+            connect(addrs),
+            head_request(&format!("https://{}", PROBE_HOST))
+                .map(|()| format!("https://{} answered", PROBE_HOST)),
+            check_mirror(),
+        ),
+        Err(_) => (
+            Err(anyhow!("skipped, DNS failed")),
+            Err(anyhow!("skipped, DNS failed")),
+            Err(anyhow!("skipped, DNS failed")),
+        ),
+    };
+    vec![
+        Probe {
+            name: "DNS",
+            result: dns.map(|addrs| {
+                let ips: Vec<String> = addrs.iter().map(|addr| addr.ip().to_string()).collect();
+                format!("{} resolves to {}", PROBE_HOST, ips.join(", "))
+            }),
+        },
+        Probe {
+            name: "TCP",
+            result: tcp,
+        },
+        Probe {
+            name: "HTTPS",
+            result: https,
+        },
+        Probe {
+            name: "Mirror",
+            result: mirror,
+        },
+    ]
+}
+
+/// First failed probe as an error, for callers that only need pass/fail.
+pub fn require_online() -> Result<()> {
+    for probe in diagnose() {
+        if let Err(err) = probe.result {
+            bail!("Network check failed at {}: {:#}", probe.name, err);
+        }
+    }
+    Ok(())
+}
+
+fn resolve(host: &str) -> Result<Vec<SocketAddr>> {
+    let (tx, rx) = mpsc::channel();
+    let target = format!("{}:443", host);
+    thread::spawn(move || {
+        let _ = tx.send(target.to_socket_addrs().map(Iterator::collect::<Vec<_>>));
+    });
+    match rx.recv_timeout(DNS_TIMEOUT) {
+        Ok(Ok(addrs)) if !addrs.is_empty() => Ok(addrs),
+        Ok(Ok(_)) => bail!("{} has no addresses", host),
+        Ok(Err(err)) => Err(err).with_context(|| format!("Failed to resolve {}", host)),
+        Err(_) => bail!("Resolving {} timed out", host),
+    }
+}
+
+/// Connect to each address in turn, so an unreachable IPv6 address on a
+/// dual-stack host does not fail the probe while IPv4 works.
+fn connect(addrs: &[SocketAddr]) -> Result<String> {
+    let mut failures = Vec::new();
+    for addr in addrs {
+        match TcpStream::connect_timeout(addr, TCP_TIMEOUT) {
+            Ok(_) => return Ok(format!("connected to {}", addr)),
+            Err(err) => failures.push(format!("{}: {}", addr, err)),
+        }
+    }
+    bail!("Failed to connect to any address ({})", failures.join("; "))
+}
+
+fn head_request(url: &str) -> Result<()> {
+    let status = Command::new("curl")
+        .args(["-sSfI", "--max-time", HTTPS_TIMEOUT_SECS, url])
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .context("Failed to run curl")?;
+    if !status.success() {
+        bail!("{} did not answer over HTTPS", url);
+    }
+    Ok(())
+}
+
+fn check_mirror() -> Result<String> {
+    let mirrorlist = fs::read_to_string(MIRRORLIST_PATH)
+        .with_context(|| format!("Failed to read {}", MIRRORLIST_PATH))?;
+    let url = first_mirror_db_url(&mirrorlist)
+        .ok_or_else(|| anyhow!("No active Server line in {}", MIRRORLIST_PATH))?;
+    head_request(&url)?;
+    Ok(format!("{} answered", url))
+}
+
+/// URL of the core repo database on the first enabled mirror.
+fn first_mirror_db_url(mirrorlist: &str) -> Option<String> {
+    mirrorlist.lines().find_map(|line| {
+        let (key, value) = line.trim().split_once('=')?;
+        if key.trim() != "Server" {
+            return None;
+        }
+        let base = value
+            .trim()
+            .replace("$repo", "core")
+            .replace("$arch", std::env::consts::ARCH);
+        Some(format!("{}/core.db", base.trim_end_matches('/')))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{connect, first_mirror_db_url};
+    use std::net::TcpListener;
+
+    #[test]
+    fn first_mirror_skips_comments_and_expands_variables() {
+        let list = "## Worldwide\n#Server = https://old.example/$repo/os/$arch\nServer = https://geo.mirror.pkgbuild.com/$repo/os/$arch\nServer = https://other.example/$repo/os/$arch\n";
+        let url = first_mirror_db_url(list).unwrap();
+
+        assert!(url.starts_with("https://geo.mirror.pkgbuild.com/core/os/"));
+        assert!(url.ends_with("/core.db"));
+        assert!(first_mirror_db_url("# nothing here\n").is_none());
+    }
+
+    #[test]
+    fn connect_falls_back_to_later_addresses() {
+        let closed = TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap();
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let open = listener.local_addr().unwrap();
+
+        let detail = connect(&[closed, open]).unwrap();
+
+        assert_eq!(detail, format!("connected to {}", open));
+        assert!(connect(&[closed]).is_err());
+    }
+}
@@ -0,0 +1,140 @@
+use super::TempPath;
+use anyhow::{anyhow, Context, Result};
+use std::fs;
+use std::os::unix::fs::PermissionsExt;
+use std::path::{Path, PathBuf};
+
+/// How a system file should be replaced.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct WriteOptions {
+    /// Copy the current file to `<path>.slate.bak` before replacing it.
+    pub backup: bool,
+    /// Permissions for the new file. Defaults to the replaced file's mode, or 0644.
+    pub mode: Option<u32>,
+}
+
+impl WriteOptions {
+    pub fn backup() -> Self {
+        Self {
+            backup: true,
+            mode: None,
+        }
+    }
+
+    pub fn mode(mode: u32) -> Self {
+        Self {
+            backup: false,
+            mode: Some(mode),
+        }
+    }
+}
+
+/// Replace a system-owned file (under /etc, /boot, ...) atomically.
+///
+/// The content is written to a temp file in the same directory and renamed
+/// over the target, so readers never observe a partially written file.
+pub fn write<P: AsRef<Path>, C: AsRef<[u8]>>(
+    path: P,
+    contents: C,
+    options: WriteOptions,
+) -> Result<()> {
+    let path = path.as_ref();
+    let parent = path
+        .parent()
+        .ok_or_else(|| anyhow!("Invalid write target {}", path.display()))?;
+    fs::create_dir_all(parent).with_context(|| format!("Failed to create {}", parent.display()))?;
+
+    let existing_mode = fs::metadata(path)
+        .ok()
+        .map(|meta| meta.permissions().mode() & 0o7777);
+    if options.backup && existing_mode.is_some() {
+        backup(path)?;
+    }
+
+    let tmp = TempPath::new(temp_path(path)?);
+    fs::write(tmp.path(), contents.as_ref())
+        .with_context(|| format!("Failed to write {}", tmp.path().display()))?;
+    let mode = options.mode.or(existing_mode).unwrap_or(0o644);
+    fs::set_permissions(tmp.path(), fs::Permissions::from_mode(mode))
+        .with_context(|| format!("Failed to set permissions on {}", tmp.path().display()))?;
+    fs::rename(tmp.path(), path)
+        .with_context(|| format!("Failed to replace {}", path.display()))?;
+    tmp.persist();
+    Ok(())
+}
+
+/// Copy `path` to `<path>.slate.bak`, overwriting any older backup.
+pub fn backup(path: &Path) -> Result<PathBuf> {
+    let backup_path = suffixed(path, ".slate.bak")?;
+    fs::copy(path, &backup_path).with_context(|| {
+        format!(
+            "Failed to back up {} to {}",
+            path.display(),
+            backup_path.display()
+        )
+    })?;
+    Ok(backup_path)
+}
+
+fn temp_path(path: &Path) -> Result<PathBuf> {
+    let name = path
+        .file_name()
+        .ok_or_else(|| anyhow!("Invalid write target {}", path.display()))?;
+    Ok(path.with_file_name(format!(".{}.slate-tmp", name.to_string_lossy())))
+}
+
+fn suffixed(path: &Path, suffix: &str) -> Result<PathBuf> {
+    let name = path
+        .file_name()
+        .ok_or_else(|| anyhow!("Invalid path {}", path.display()))?;
+    Ok(path.with_file_name(format!("{}{}", name.to_string_lossy(), suffix)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{write, WriteOptions};
+    use std::fs;
+    use std::os::unix::fs::PermissionsExt;
+
+    #[test]
+    fn write_replaces_file_and_keeps_mode() {
+        let dir = std::env::temp_dir().join(format!("slate-priv-test-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let target = dir.join("sudoers");
+        fs::write(&target, "old\n").unwrap();
+        fs::set_permissions(&target, fs::Permissions::from_mode(0o440)).unwrap();
+
+        write(&target, "new\n", WriteOptions::backup()).unwrap();
+
+        let content = fs::read_to_string(&target).unwrap();
+        let mode = fs::metadata(&target).unwrap().permissions().mode() & 0o7777;
+        let backup = fs::read_to_string(dir.join("sudoers.slate.bak")).unwrap();
+        let leftover = dir.join(".sudoers.slate-tmp").exists();
+        let _ = fs::remove_dir_all(&dir);
+
+        assert_eq!(content, "new\n");
+        assert_eq!(mode, 0o440);
+        assert_eq!(backup, "old\n");
+        assert!(!leftover);
+    }
+
+    #[test]
+    fn write_creates_missing_file_with_requested_mode() {
+        let dir = std::env::temp_dir().join(format!("slate-priv-new-{}", std::process::id()));
+        let target = dir.join("sudoers.d/10-user");
+
+        write(
+            &target,
+            "user ALL=(ALL:ALL) ALL\n",
+            WriteOptions::mode(0o440),
+        )
+        .unwrap();
+
+        let mode = fs::metadata(&target).unwrap().permissions().mode() & 0o7777;
+        let has_backup = dir.join("sudoers.d/10-user.slate.bak").exists();
+        let _ = fs::remove_dir_all(&dir);
+
+        assert_eq!(mode, 0o440);
+        assert!(!has_backup);
+    }
+}
@@ -0,0 +1,91 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// A scratch file or directory that is removed when the guard drops, unless it
+/// was persisted.
+///
+/// Keeps error paths (`?`, `bail!`) between creating a temp file and handing it
+/// off from leaving stray files behind. Directories are removed with their
+/// contents; a symlink is removed itself, never its target.
+#[derive(Debug)]
+pub struct TempPath {
+    path: PathBuf,
+    armed: bool,
+}
+
+impl TempPath {
+    pub fn new<P: Into<PathBuf>>(path: P) -> Self {
+        Self {
+            path: path.into(),
+            armed: true,
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Keep the file (or accept that it was renamed away) and disarm the guard.
+    pub fn persist(mut self) -> PathBuf {
+        self.armed = false;
+        std::mem::take(&mut self.path)
+    }
+
+    /// Remove the path now, reporting failures that `Drop` would swallow.
+    pub fn close(mut self) -> io::Result<()> {
+        self.armed = false;
+        match remove(&self.path) {
+            Err(err) if err.kind() != io::ErrorKind::NotFound => Err(err),
+            _ => Ok(()),
+        }
+    }
+}
+
+impl Drop for TempPath {
+    fn drop(&mut self) {
+        if self.armed {
+            let _ = remove(&self.path);
+        }
+    }
+}
+
+fn remove(path: &Path) -> io::Result<()> {
+    if fs::symlink_metadata(path)?.is_dir() {
+        fs::remove_dir_all(path)
+    } else {
+        fs::remove_file(path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::TempPath;
+    use std::fs;
+
+    #[test]
+    fn drop_removes_file_unless_persisted() {
+        let dir = std::env::temp_dir().join(format!("slate-temp-test-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let dropped = dir.join("dropped");
+        let kept = dir.join("kept");
+        let tree = dir.join("tree");
+        fs::write(&dropped, "x").unwrap();
+        fs::write(&kept, "x").unwrap();
+        fs::create_dir_all(tree.join("nested")).unwrap();
+        fs::write(tree.join("nested/file"), "x").unwrap();
+
+        drop(TempPath::new(&dropped));
+        drop(TempPath::new(&tree));
+        let persisted = TempPath::new(&kept).persist();
+
+        let dropped_exists = dropped.exists();
+        let tree_exists = tree.exists();
+        let kept_exists = persisted.exists();
+        let _ = fs::remove_dir_all(&dir);
+
+        assert!(!dropped_exists);
+        assert!(!tree_exists);
+        assert!(kept_exists);
+    }
+}
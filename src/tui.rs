@@ -1,6 +1,9 @@
-use crate::installer::{self, EventSink, InstallEvent, InstallPlan, StageId};
+use crate::error::SlateError;
+use crate::installer::{
+    self, EventSink, InstallEvent, InstallOptions, InstallPlan, StageId, PLAN_VERSION,
+};
 use crate::system::BlockDevice;
-use anyhow::Result;
+use anyhow::{anyhow, bail, Result};
 use crossterm::{
     event::{self, Event, KeyCode, KeyEventKind},
     execute,
@@ -19,17 +22,20 @@ use std::sync::mpsc::{self, Receiver};
 use std::thread;
 use std::time::Duration;
 
-const FORM_FIELDS: usize = 9;
+const FORM_FIELDS: usize = 12;
 
 #[derive(Clone)]
 pub struct UserInfo {
     pub hostname: String,
     pub username: String,
     pub password: String,
+    pub password_confirm: String,
     pub keymap: String,
+    pub locale: String,
     pub timezone: String,
     pub git_name: String,
     pub git_email: String,
+    pub autologin: bool,
 }
 
 impl Default for UserInfo {
@@ -38,10 +44,13 @@ impl Default for UserInfo {
             hostname: String::new(),
             username: String::new(),
             password: String::new(),
+            password_confirm: String::new(),
             keymap: "us".to_string(),
+            locale: installer::default_locale(),
             timezone: "UTC".to_string(),
             git_name: String::new(),
             git_email: String::new(),
+            autologin: true,
         }
     }
 }
@@ -50,6 +59,7 @@ impl Default for UserInfo {
 enum SelectorKind {
     Disk,
     Keymap,
+    Locale,
     Timezone,
 }
 
@@ -65,9 +75,11 @@ struct App {
     screen: Screen,
     selected_field: usize,
     user_info: UserInfo,
+    options: InstallOptions,
     devices: Vec<BlockDevice>,
     selected_disk: usize,
     keymaps: Vec<String>,
+    locales: Vec<String>,
     timezones: Vec<String>,
     selector_input: String,
     selector_state: ListState,
@@ -76,6 +88,10 @@ struct App {
     rx: Option<Receiver<InstallEvent>>,
     result_message: Option<String>,
     install_failed: bool,
+    /// Stage that failed, which decides the exit code.
+    failed_stage: Option<StageId>,
+    /// Why the form could not continue; shown in the footer until the next key.
+    form_error: Option<String>,
 }
 
 #[derive(Clone, Copy, PartialEq, Eq)]
@@ -86,16 +102,29 @@ enum StageStatus {
 }
 
 impl App {
-    fn new(devices: Vec<BlockDevice>, keymaps: Vec<String>, timezones: Vec<String>) -> Self {
+    fn new(
+        devices: Vec<BlockDevice>,
+        options: InstallOptions,
+        keymaps: Vec<String>,
+        locales: Vec<String>,
+        timezones: Vec<String>,
+    ) -> Self {
         let mut selector_state = ListState::default();
         selector_state.select(Some(0));
+        let user_info = UserInfo {
+            hostname: options.hostname.clone().unwrap_or_default(),
+            username: options.username.clone().unwrap_or_default(),
+            ..UserInfo::default()
+        };
         Self {
             screen: Screen::Plan,
             selected_field: 0,
-            user_info: UserInfo::default(),
+            user_info,
+            options,
             devices,
             selected_disk: 0,
             keymaps,
+            locales,
             timezones,
             selector_input: String::new(),
             selector_state,
@@ -107,6 +136,8 @@ impl App {
             rx: None,
             result_message: None,
             install_failed: false,
+            failed_stage: None,
+            form_error: None,
         }
     }
 
@@ -125,8 +156,19 @@ impl App {
             git_name: self.user_info.git_name.clone(),
             git_email: self.user_info.git_email.clone(),
             desktop_profile: "Slate".to_string(),
+            locale: self.user_info.locale.clone(),
+            subvolumes: self.options.subvolumes.clone(),
+            autologin: self.user_info.autologin,
+            preserve_esp: self.options.preserve_esp,
+            btrfs_options: self.options.btrfs_options.clone(),
+            boot_title: self.options.boot_title.clone(),
+            boot_timeout: self.options.boot_timeout,
+            version: PLAN_VERSION,
         };
         plan.validate()?;
+        if self.user_info.password != self.user_info.password_confirm {
+            bail!("Passwords do not match");
+        }
         Ok(plan)
     }
 
@@ -155,6 +197,7 @@ impl App {
                 .map(|disk| format!("{}  {}  {}", disk.path, disk.size, disk.model))
                 .collect(),
             SelectorKind::Keymap => self.keymaps.clone(),
+            SelectorKind::Locale => self.locales.clone(),
             SelectorKind::Timezone => self.timezones.clone(),
         };
 
@@ -182,8 +225,10 @@ impl App {
     }
 }
 
-pub fn run_installer(devices: Vec<BlockDevice>) -> Result<()> {
+pub fn run_installer(devices: Vec<BlockDevice>, options: InstallOptions) -> Result<()> {
     let keymaps = crate::system::list_keymaps().unwrap_or_else(|_| vec!["us".to_string()]);
+    let locales =
+        crate::system::list_locales().unwrap_or_else(|_| vec![installer::default_locale()]);
     let timezones = crate::system::list_timezones().unwrap_or_else(|_| vec!["UTC".to_string()]);
 
     enable_raw_mode()?;
@@ -191,7 +236,10 @@ pub fn run_installer(devices: Vec<BlockDevice>) -> Result<()> {
     execute!(stdout, EnterAlternateScreen)?;
     let backend = CrosstermBackend::new(stdout);
     let mut terminal = Terminal::new(backend)?;
-    let result = run_loop(&mut terminal, App::new(devices, keymaps, timezones));
+    let result = run_loop(
+        &mut terminal,
+        App::new(devices, options, keymaps, locales, timezones),
+    );
     disable_raw_mode()?;
     execute!(terminal.backend_mut(), LeaveAlternateScreen)?;
     terminal.show_cursor()?;
@@ -229,10 +277,19 @@ fn run_loop(terminal: &mut Terminal<CrosstermBackend<io::Stdout>>, mut app: App)
         }
     }
 
+    if app.install_failed {
+        let message = app.result_message.unwrap_or_default();
+        let class = match app.failed_stage {
+            Some(stage) => stage.error_class(),
+            None => SlateError::Install,
+        };
+        return Err(class(anyhow!("Install failed: {}", message)).into());
+    }
     Ok(())
 }
 
 fn handle_plan_keys(app: &mut App, code: KeyCode) -> Result<()> {
+    app.form_error = None;
     match code {
         KeyCode::Up => {
             if app.selected_field == 0 {
@@ -246,12 +303,14 @@ fn handle_plan_keys(app: &mut App, code: KeyCode) -> Result<()> {
         }
         KeyCode::Enter => match app.selected_field {
             0 => enter_selector(app, SelectorKind::Disk),
-            4 => enter_selector(app, SelectorKind::Keymap),
-            5 => enter_selector(app, SelectorKind::Timezone),
-            8 => {
-                app.build_plan()?;
-                app.screen = Screen::Review;
-            }
+            5 => enter_selector(app, SelectorKind::Keymap),
+            6 => enter_selector(app, SelectorKind::Locale),
+            7 => enter_selector(app, SelectorKind::Timezone),
+            10 => app.user_info.autologin = !app.user_info.autologin,
+            11 => match app.build_plan() {
+                Ok(_) => app.screen = Screen::Review,
+                Err(err) => app.form_error = Some(format!("{:#}", err)),
+            },
             _ => {}
         },
         KeyCode::Backspace => {
@@ -260,6 +319,9 @@ fn handle_plan_keys(app: &mut App, code: KeyCode) -> Result<()> {
             }
         }
         KeyCode::Esc | KeyCode::Char('q') => std::process::exit(0),
+        KeyCode::Char(' ') if app.selected_field == 10 => {
+            app.user_info.autologin = !app.user_info.autologin;
+        }
         KeyCode::Char(ch) => {
             if let Some(field) = current_text_field(app) {
                 if !field.read_only {
@@ -317,6 +379,7 @@ fn handle_selector_keys(app: &mut App, code: KeyCode) {
                         }
                     }
                     SelectorKind::Keymap => app.user_info.keymap = value.clone(),
+                    SelectorKind::Locale => app.user_info.locale = value.clone(),
                     SelectorKind::Timezone => app.user_info.timezone = value.clone(),
                 }
                 app.screen = Screen::Plan;
@@ -378,6 +441,7 @@ fn drain_events(app: &mut App) {
                 }
                 InstallEvent::Failed { stage, message } => {
                     app.install_failed = true;
+                    app.failed_stage = stage;
                     app.result_message = Some(match stage {
                         Some(stage) => format!("{}: {}", stage.label(), message),
                         None => message,
@@ -453,23 +517,31 @@ fn render_plan(frame: &mut Frame<'_>, area: ratatui::layout::Rect, app: &App) {
 
     let disk_label = app.selected_disk_label();
     let password_mask = "*".repeat(app.user_info.password.chars().count());
+    let confirm_mask = "*".repeat(app.user_info.password_confirm.chars().count());
     let items = vec![
         field_line("Disk", &disk_label, app.selected_field == 0),
         field_line("Hostname", &app.user_info.hostname, app.selected_field == 1),
         field_line("Username", &app.user_info.username, app.selected_field == 2),
         field_line("Password", &password_mask, app.selected_field == 3),
-        field_line("Keymap", &app.user_info.keymap, app.selected_field == 4),
-        field_line("Timezone", &app.user_info.timezone, app.selected_field == 5),
-        field_line("Git Name", &app.user_info.git_name, app.selected_field == 6),
+        field_line("Confirm", &confirm_mask, app.selected_field == 4),
+        field_line("Keymap", &app.user_info.keymap, app.selected_field == 5),
+        field_line("Locale", &app.user_info.locale, app.selected_field == 6),
+        field_line("Timezone", &app.user_info.timezone, app.selected_field == 7),
+        field_line("Git Name", &app.user_info.git_name, app.selected_field == 8),
         field_line(
             "Git Email",
             &app.user_info.git_email,
-            app.selected_field == 7,
+            app.selected_field == 9,
+        ),
+        field_line(
+            "Autologin",
+            autologin_label(app.user_info.autologin),
+            app.selected_field == 10,
         ),
         field_line(
             "Continue",
             "Review destructive summary",
-            app.selected_field == 8,
+            app.selected_field == 11,
         ),
     ];
     let list = List::new(items).block(
@@ -480,8 +552,13 @@ fn render_plan(frame: &mut Frame<'_>, area: ratatui::layout::Rect, app: &App) {
     );
     frame.render_widget(list, rows[1]);
 
-    let footer = Paragraph::new("Up/Down: move  Enter: select/open  Esc: quit")
-        .style(Style::default().fg(Color::Rgb(150, 160, 170)));
+    let footer = match &app.form_error {
+        Some(error) => {
+            Paragraph::new(error.as_str()).style(Style::default().fg(Color::Rgb(230, 110, 90)))
+        }
+        None => Paragraph::new("Up/Down: move  Enter: select/open  Esc: quit")
+            .style(Style::default().fg(Color::Rgb(150, 160, 170))),
+    };
     frame.render_widget(footer, rows[2]);
 }
 
@@ -501,6 +578,7 @@ fn render_selector(
     let title = match kind {
         SelectorKind::Disk => "Select Disk",
         SelectorKind::Keymap => "Select Keymap",
+        SelectorKind::Locale => "Select Locale",
         SelectorKind::Timezone => "Select Timezone",
     };
     let layout = Layout::default()
@@ -528,20 +606,47 @@ fn render_selector(
 }
 
 fn render_review(frame: &mut Frame<'_>, area: ratatui::layout::Rect, app: &App) {
+    let (warning, esp) = if app.options.preserve_esp {
+        (
+            "This keeps partition 1 (existing ESP) and deletes every other partition.",
+            "existing ESP",
+        )
+    } else {
+        ("This will wipe the selected disk.", "1G EFI")
+    };
     let text = vec![
         Line::from(Span::styled(
-            "This will wipe the selected disk.",
+            warning,
             Style::default()
                 .fg(Color::Rgb(230, 110, 90))
                 .add_modifier(Modifier::BOLD),
         )),
         Line::from(""),
         Line::from(format!("Disk: {}", app.selected_disk_label())),
-        Line::from("Layout: 1G EFI + remaining Btrfs with @, @home, @log, @pkg, @snapshots"),
+        Line::from(format!(
+            "Layout: {} + remaining Btrfs with {}",
+            esp,
+            app.options
+                .subvolumes
+                .iter()
+                .map(|subvol| format!("{} ({})", subvol.name, subvol.mountpoint))
+                .collect::<Vec<_>>()
+                .join(", ")
+        )),
+        Line::from(format!("Mount options: {}", app.options.btrfs_options)),
+        Line::from(format!(
+            "Boot entry: {} (menu timeout {}s)",
+            app.options.boot_title, app.options.boot_timeout
+        )),
         Line::from(format!("Hostname: {}", app.user_info.hostname)),
         Line::from(format!("User: {}", app.user_info.username)),
         Line::from(format!("Keymap: {}", app.user_info.keymap)),
+        Line::from(format!("Locale: {}", app.user_info.locale)),
         Line::from(format!("Timezone: {}", app.user_info.timezone)),
+        Line::from(format!(
+            "Autologin: {}",
+            autologin_label(app.user_info.autologin)
+        )),
         Line::from("Desktop: Slate (Hyprland + shell assets)"),
         Line::from(""),
         Line::from("Enter to start install. Esc to go back."),
@@ -669,8 +774,8 @@ struct FieldMeta {
 
 fn current_text_field(app: &App) -> Option<FieldMeta> {
     match app.selected_field {
-        1 | 2 | 3 | 6 | 7 => Some(FieldMeta { read_only: false }),
-        0 | 4 | 5 | 8 => Some(FieldMeta { read_only: true }),
+        1 | 2 | 3 | 4 | 8 | 9 => Some(FieldMeta { read_only: false }),
+        0 | 5 | 6 | 7 | 10 | 11 => Some(FieldMeta { read_only: true }),
         _ => None,
     }
 }
@@ -680,12 +785,21 @@ fn current_text_field_mut(app: &mut App) -> Option<&mut String> {
         1 => Some(&mut app.user_info.hostname),
         2 => Some(&mut app.user_info.username),
         3 => Some(&mut app.user_info.password),
-        6 => Some(&mut app.user_info.git_name),
-        7 => Some(&mut app.user_info.git_email),
+        4 => Some(&mut app.user_info.password_confirm),
+        8 => Some(&mut app.user_info.git_name),
+        9 => Some(&mut app.user_info.git_email),
         _ => None,
     }
 }
 
+fn autologin_label(enabled: bool) -> &'static str {
+    if enabled {
+        "on (tty1 starts Hyprland without a password)"
+    } else {
+        "off (log in on tty1)"
+    }
+}
+
 fn enter_selector(app: &mut App, kind: SelectorKind) {
     app.selector_input.clear();
     app.selector_state.select(Some(0));
@@ -698,6 +812,7 @@ fn reset_stage_states(app: &mut App) {
     }
     app.result_message = None;
     app.install_failed = false;
+    app.failed_stage = None;
 }
 
 fn centered_rect(
@@ -722,3 +837,35 @@ fn centered_rect(
         ])
         .split(vertical[1])[1]
 }
+
+#[cfg(test)]
+mod tests {
+    use super::App;
+    use crate::installer::InstallOptions;
+    use crate::system::BlockDevice;
+
+    fn filled_app(password: &str, confirm: &str) -> App {
+        let devices = vec![BlockDevice {
+            path: "/dev/vda".to_string(),
+            size: "64G".to_string(),
+            model: "virtio".to_string(),
+        }];
+        let mut app = App::new(devices, InstallOptions::default(), vec![], vec![], vec![]);
+        app.user_info.hostname = "slatebox".to_string();
+        app.user_info.username = "alice".to_string();
+        app.user_info.password = password.to_string();
+        app.user_info.password_confirm = confirm.to_string();
+        app
+    }
+
+    #[test]
+    fn build_plan_requires_matching_password_confirmation() {
+        let mismatch = filled_app("hunter2", "hunter3").build_plan().unwrap_err();
+        assert_eq!(mismatch.to_string(), "Passwords do not match");
+        assert!(filled_app("hunter2", "").build_plan().is_err());
+
+        let plan = filled_app("hunter2", "hunter2").build_plan().unwrap();
+        assert_eq!(plan.password, "hunter2");
+        assert_eq!(plan.disk, "/dev/vda");
+    }
+}